@@ -0,0 +1,94 @@
+// Bill Derksen - 10/26
+//-- axis-aligned bounds used to cull ray tests (see bvh)
+
+use crate::stringable::{Stringable};
+use crate::ray::{Ray};
+use crate::vmaths::{Point, Vec3};
+
+//---- Aabb: box spanning min -> max corners
+#[derive(Copy, Clone)]
+pub struct Aabb{
+    pub min: Point,
+    pub max: Point
+
+} impl Aabb {
+
+    pub fn new(a: Point, b: Point) -> Aabb{
+        Aabb{min: a.min(b), max: a.max(b)}
+    }
+
+    //-- inside out box, union with anything gives that thing
+    pub fn empty() -> Aabb{
+        Aabb{min: Point::gen(f64::INFINITY, f64::INFINITY, f64::INFINITY), max: Point::gen(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY)}
+    }
+
+    //-- flat boxes (e.g. rects) get a little thickness so slab tests still see them
+    pub fn padded(&self, eps: f64) -> Aabb{
+        let mut b = *self;
+        if b.max.x - b.min.x < eps { b.min.x -= 0.5 * eps; b.max.x += 0.5 * eps; }
+        if b.max.y - b.min.y < eps { b.min.y -= 0.5 * eps; b.max.y += 0.5 * eps; }
+        if b.max.z - b.min.z < eps { b.min.z -= 0.5 * eps; b.max.z += 0.5 * eps; }
+        b
+    }
+
+    pub fn union(&self, other: &Aabb) -> Aabb{
+        Aabb{min: self.min.min(other.min), max: self.max.max(other.max)}
+    }
+
+    pub fn grow(&self, p: Point) -> Aabb{
+        Aabb{min: self.min.min(p), max: self.max.max(p)}
+    }
+
+    pub fn centroid(&self) -> Point{
+        0.5 * (self.min + self.max)
+    }
+
+    pub fn extent(&self) -> Vec3{
+        self.max - self.min
+    }
+
+    //-- 0 = x, 1 = y, 2 = z
+    pub fn longest_axis(&self) -> usize{
+        let e = self.extent();
+        if e.x >= e.y && e.x >= e.z { 0 } else if e.y >= e.z { 1 } else { 2 }
+    }
+
+    pub fn surface_area(&self) -> f64{
+        let e = self.extent();
+        if e.x < 0.0 || e.y < 0.0 || e.z < 0.0 {
+            return 0.0;
+        }
+        2.0 * (e.x * e.y + e.y * e.z + e.z * e.x)
+    }
+
+    //-- slab test: entry t of the ray into the box, if it overlaps [t_min, t_max]
+    //- inv_dir = 1 / ray.dir per component, precomputed by the caller
+    pub fn hit(&self, ray: &Ray, inv_dir: Vec3, t_min: f64, t_max: f64) -> Option<f64>{
+        let mut t0: f64 = t_min;
+        let mut t1: f64 = t_max;
+        for axis in 0..3 {
+
+            //-- parallel to this slab: inside it everywhere or nowhere (0 * inf would be NaN on a box plane)
+            if ray.dir[axis] == 0.0 {
+                if ray.origin[axis] < self.min[axis] || ray.origin[axis] > self.max[axis] {
+                    return None;
+                }
+                continue;
+            }
+
+            let ta = (self.min[axis] - ray.origin[axis]) * inv_dir[axis];
+            let tb = (self.max[axis] - ray.origin[axis]) * inv_dir[axis];
+            t0 = t0.max(ta.min(tb));
+            t1 = t1.min(ta.max(tb));
+            if t1 < t0 {
+                return None;
+            }
+        }
+        Some(t0)
+    }
+
+} impl Stringable for Aabb{
+    fn stringy(&self) -> String{
+        "min: ".to_owned() + &self.min.stringy() + ", max: " + &self.max.stringy()
+    }
+}
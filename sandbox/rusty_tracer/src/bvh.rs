@@ -0,0 +1,244 @@
+// Bill Derksen - 10/26
+//-- bounding volume hierarchy: SAH-split tree over hittables, stored as a flat node array
+
+use crate::aabb::{Aabb};
+use crate::hittable::{Hittable, HitInfo};
+use crate::ray::{Ray};
+use crate::stringable::{Stringable};
+use crate::vmaths::{Point, Vec3};
+
+const SAH_BINS: usize = 12;
+const MAX_LEAF_SIZE: usize = 4;
+const TRAVERSAL_COST: f64 = 1.0;        //-- cost of visiting a node, relative to one primitive test
+
+//---- Bvh Node: leaves hold objects[offset..offset + count], interior nodes have count == 0,
+//- their first child right after them in the array and their second child at offset
+#[derive(Copy, Clone)]
+struct BvhNode{
+    bounds: Aabb,
+    offset: usize,
+    count: usize,
+    axis: usize
+}
+
+//-- per-object info used while building
+#[derive(Copy, Clone)]
+struct BuildPrim{
+    index: usize,
+    bounds: Aabb,
+    centroid: Point
+}
+
+//---- Bvh: owns its objects (reordered so every leaf is a contiguous run) and behaves like any other hittable
+pub struct Bvh{
+    objects: Vec<Box<dyn Hittable>>,
+    nodes: Vec<BvhNode>
+
+} impl Bvh {
+
+    pub fn build(objects: Vec<Box<dyn Hittable>>) -> Bvh{
+
+        let mut prims: Vec<BuildPrim> = objects.iter().enumerate().map(|(i, obj)| {
+            let bounds = obj.bounding_box();
+            BuildPrim{index: i, bounds, centroid: bounds.centroid()}
+        }).collect();
+
+        let mut nodes: Vec<BvhNode> = Vec::with_capacity(2 * prims.len());
+        if !prims.is_empty() {
+            let n = prims.len();
+            build_node(&mut prims, 0, n, &mut nodes);
+        }
+
+        //-- reorder objects to match the leaf ranges
+        let mut slots: Vec<Option<Box<dyn Hittable>>> = objects.into_iter().map(Some).collect();
+        let objects = prims.iter().map(|p| slots[p.index].take().unwrap()).collect();
+
+        Bvh{objects, nodes}
+    }
+
+    pub fn len(&self) -> usize{
+        self.objects.len()
+    }
+
+    pub fn is_empty(&self) -> bool{
+        self.objects.is_empty()
+    }
+
+    pub fn node_count(&self) -> usize{
+        self.nodes.len()
+    }
+
+} impl Hittable for Bvh {
+
+    //-- closest hit in range, skipping any node that starts past the best hit so far
+    fn hits(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitInfo<'_>>{
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        let inv_dir = Vec3::gen(1.0 / ray.dir.x, 1.0 / ray.dir.y, 1.0 / ray.dir.z);
+        let mut closest: Option<HitInfo> = None;
+        let mut closest_t = t_max;
+
+        let mut stack: Vec<usize> = Vec::with_capacity(64);
+        stack.push(0);
+        while let Some(i) = stack.pop() {
+            let node = &self.nodes[i];
+            if node.bounds.hit(ray, inv_dir, t_min, closest_t).is_none() {
+                continue;
+            }
+
+            if node.count > 0 {
+                for obj in &self.objects[node.offset..node.offset + node.count] {
+                    if let Some(hit_rec) = obj.hits(ray, t_min, closest_t) {
+                        closest_t = hit_rec.t;
+                        closest = Some(hit_rec);
+                    }
+                }
+            } else if ray.dir[node.axis] < 0.0 {           //-- visit the near child first
+                stack.push(i + 1);
+                stack.push(node.offset);
+            } else {
+                stack.push(node.offset);
+                stack.push(i + 1);
+            }
+        }
+        closest
+    }
+
+    //-- same walk as hits, but done as soon as anything is in the way
+    fn hits_any(&self, ray: &Ray, t_min: f64, t_max: f64) -> bool{
+        if self.nodes.is_empty() {
+            return false;
+        }
+
+        let inv_dir = Vec3::gen(1.0 / ray.dir.x, 1.0 / ray.dir.y, 1.0 / ray.dir.z);
+        let mut stack: Vec<usize> = Vec::with_capacity(64);
+        stack.push(0);
+        while let Some(i) = stack.pop() {
+            let node = &self.nodes[i];
+            if node.bounds.hit(ray, inv_dir, t_min, t_max).is_none() {
+                continue;
+            }
+
+            if node.count > 0 {
+                if self.objects[node.offset..node.offset + node.count].iter().any(|obj| obj.hits_any(ray, t_min, t_max)) {
+                    return true;
+                }
+            } else {
+                stack.push(node.offset);
+                stack.push(i + 1);
+            }
+        }
+        false
+    }
+
+    fn get_pos(&self) -> Point{
+        self.bounding_box().centroid()
+    }
+
+    fn bounding_box(&self) -> Aabb{
+        match self.nodes.first() {
+            Some(root) => root.bounds,
+            None => Aabb::empty()
+        }
+    }
+
+} impl Stringable for Bvh{
+    fn stringy(&self) -> String{
+        self.len().to_string() + " objects, " + &self.node_count().to_string() + " nodes, bounds " + &self.bounding_box().stringy()
+    }
+}
+
+// <<<<<<<<<<<<<<<<<<<<  BUILD HELPERS >>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>
+
+//-- build the subtree over prims[start..end], returns its node index
+fn build_node(prims: &mut [BuildPrim], start: usize, end: usize, nodes: &mut Vec<BvhNode>) -> usize{
+
+    let bounds = prims[start..end].iter().fold(Aabb::empty(), |b, p| b.union(&p.bounds));
+    let index = nodes.len();
+    nodes.push(BvhNode{bounds, offset: start, count: end - start, axis: 0});
+
+    let count = end - start;
+    if count <= 1 {
+        return index;
+    }
+
+    let centroid_bounds = prims[start..end].iter().fold(Aabb::empty(), |b, p| b.grow(p.centroid));
+    let axis = centroid_bounds.longest_axis();
+    let lo = centroid_bounds.min[axis];
+    let span = centroid_bounds.max[axis] - lo;
+
+    //-- all centroids in one spot: nothing to split on
+    if span <= 0.0 {
+        return index;
+    }
+
+    //-- bin centroids along the axis, then score each bin boundary with the surface area heuristic
+    let bin_of = |p: &BuildPrim| -> usize{
+        (((p.centroid[axis] - lo) / span * SAH_BINS as f64) as usize).min(SAH_BINS - 1)
+    };
+    let mut bin_bounds = [Aabb::empty(); SAH_BINS];
+    let mut bin_counts = [0usize; SAH_BINS];
+    for p in prims[start..end].iter() {
+        let b = bin_of(p);
+        bin_counts[b] += 1;
+        bin_bounds[b] = bin_bounds[b].union(&p.bounds);
+    }
+
+    //- sweep from the right to get the cost of everything past each boundary
+    let mut right_area = [0.0; SAH_BINS];
+    let mut right_count = [0usize; SAH_BINS];
+    let mut acc = Aabb::empty();
+    let mut acc_count = 0;
+    for b in (1..SAH_BINS).rev() {
+        acc = acc.union(&bin_bounds[b]);
+        acc_count += bin_counts[b];
+        right_area[b] = acc.surface_area();
+        right_count[b] = acc_count;
+    }
+
+    let mut best_cost = f64::INFINITY;
+    let mut best_split = 0;
+    let mut acc = Aabb::empty();
+    let mut acc_count = 0;
+    for b in 0..SAH_BINS - 1 {
+        acc = acc.union(&bin_bounds[b]);
+        acc_count += bin_counts[b];
+        if acc_count == 0 || right_count[b + 1] == 0 {
+            continue;
+        }
+        let cost = acc.surface_area() * acc_count as f64 + right_area[b + 1] * right_count[b + 1] as f64;
+        if cost < best_cost {
+            best_cost = cost;
+            best_split = b;
+        }
+    }
+
+    //- compare to just testing everything in a leaf
+    let parent_area = bounds.surface_area();
+    let split_cost = if parent_area > 0.0 { TRAVERSAL_COST + best_cost / parent_area } else { f64::INFINITY };
+    if count <= MAX_LEAF_SIZE && split_cost >= count as f64 {
+        return index;
+    }
+
+    //-- partition around the chosen boundary, falling back to a median split if the bins did not separate anything
+    let mut mid = start;
+    if best_cost.is_finite() {
+        for i in start..end {
+            if bin_of(&prims[i]) <= best_split {
+                prims.swap(i, mid);
+                mid += 1;
+            }
+        }
+    }
+    if mid == start || mid == end {
+        mid = start + count / 2;
+        prims[start..end].select_nth_unstable_by(mid - start, |a, b| a.centroid[axis].total_cmp(&b.centroid[axis]));
+    }
+
+    build_node(prims, start, mid, nodes);
+    let second = build_node(prims, mid, end, nodes);
+    nodes[index] = BvhNode{bounds, offset: second, count: 0, axis};
+    index
+}
@@ -1,55 +1,175 @@
 // Bill Derksen - 8/21
 //-- camera structure and utility functions
 
+use std::f64::consts::PI;
+
 use crate::stringable::{Stringable};
+use crate::ray::{Ray};
+use crate::sampler::{self, Rng};
 use crate::vmaths::{Point, Vec3};
 
+//---- Camera Model: a projection that turns image points into primary rays
+//- (s, t) are both in [0, 1]: s runs left -> right, t top -> bottom
+pub trait CameraModel: Send + Sync + Stringable{
+    fn get_ray(&self, s: f64, t: f64, rng: &mut Rng) -> Ray;
+
+    //-- where the view is taken from (the center of the lens / view plane)
+    fn eye(&self) -> Point;
+
+    //-- image width / height the projection is meant to fill
+    fn aspect(&self) -> f64;
+}
+
+//---- Camera: perspective projection, looks from eye toward target, with a vertical field of view (degrees) and width / height aspect
+//- build with look_at, which works out the orthonormal basis the rays are generated from
+//- thin lens: rays start across a disk of radius aperture around the eye and meet again focus_dist ahead,
+//- so only things at that distance are sharp. aperture 0 is a pinhole, everything sharp
+#[derive(Copy, Clone)]
 pub struct Camera{
-    pub pos: Point,
-    pub focl: f64,
-    pub w: f64,
-    pub h: f64,
-
-    /*
-    pub near: f64,      //-- frustum vals
-    pub far: f64,
-    pub top: f64,
-    pub bottom: f64,
-    pub left: f64,
-    pub right: f64,
-    pub look_at: Point */
+    pub eye: Point,
+    pub target: Point,
+    pub up: Vec3,
+    pub vfov: f64,
+    pub aspect: f64,
+    pub aperture: f64,
+    pub focus_dist: f64,
+
+    //-- basis: right, true up and forward unit vectors, plus the half extents of the image plane 1 unit ahead
+    right: Vec3,
+    cam_up: Vec3,
+    forward: Vec3,
+    half_w: f64,
+    half_h: f64
 
 } impl Camera{
 
+    //-- default framing of the Cornell box scenes, from 30 units back along -z
     pub fn new() -> Camera{
-        Camera {pos: Point{x:0.0, y:0.0, z: -30.0}, focl: 1.0, w: 16.0, h: 9.0 }
-        /*
-        Camera{ 
-            pos: Point{x:0.0, y:0.0, z: -16.0}, focl: 1.0, w: 16.0, h: 9.0 , near: 1.0, far: 40.0, top: 4.5, bottom: -4.5, left: -8.0, right: 8.0, look_at: Point::new()
-        }
-        */
-    }
-
-    /*
-    pub fn gen(position: Point, focal_l: f64, width: f64, height: f64, n: f64, f: f64) -> Camera{
-        Camera{ 
-            pos: position, 
-            focl: focal_l,
-            w: width,
-            h: height,
-            near: n,
-            far: f,
-            top: 0.5 * height,
-            bottom: -0.5 * height,
-            left: -0.5 * width,
-            right: 0.5 * width,
-            look_at: Point::new()
+        let vfov = 2.0 * (4.5_f64 / 29.0).atan().to_degrees();
+        Camera::look_at(Point::gen(0.0, 0.0, -30.0), Point::gen(0.0, 0.0, 0.0), Vec3::gen(0.0, 1.0, 0.0), vfov, 16.0 / 9.0)
+    }
+
+    //-- pinhole camera at eye looking at target; up only needs to be roughly up (not parallel to the view direction)
+    pub fn look_at(eye: Point, target: Point, up: Vec3, vfov: f64, aspect: f64) -> Camera{
+        let forward = (target - eye).unit();
+        let right = up.cross(forward).unit();
+        let cam_up = forward.cross(right);
+        let half_h = (0.5 * vfov.to_radians()).tan();
+        let half_w = aspect * half_h;
+        Camera{eye, target, up, vfov, aspect, aperture: 0.0, focus_dist: (target - eye).mag(), right, cam_up, forward, half_w, half_h}
+    }
+
+    //-- same camera with a thin lens of the given radius, focused focus_dist along the view direction
+    pub fn with_lens(self, aperture: f64, focus_dist: f64) -> Camera{
+        Camera{aperture, focus_dist, ..self}
+    }
+
+    //-- eye == target or up along the view direction leave no basis to build rays from
+    pub fn is_degenerate(&self) -> bool{
+        self.right.mag().is_nan() || self.forward.mag().is_nan()
+    }
+
+    //-- (right, up, forward) unit vectors of the view
+    pub fn basis(&self) -> (Vec3, Vec3, Vec3){
+        (self.right, self.cam_up, self.forward)
+    }
+
+    //-- height of the view at the target distance, e.g. to frame an orthographic view like this one
+    pub fn view_height(&self) -> f64{
+        2.0 * self.half_h * (self.target - self.eye).mag()
+    }
+
+} impl CameraModel for Camera{
+
+    //-- with an aperture, the ray starts at a random point on the lens and passes through the in-focus point
+    fn get_ray(&self, s: f64, t: f64, rng: &mut Rng) -> Ray{
+        let dir = self.forward + ((2.0 * s - 1.0) * self.half_w) * self.right + ((1.0 - 2.0 * t) * self.half_h) * self.cam_up;
+        if self.aperture <= 0.0 {
+            return Ray{origin: self.eye, dir};
         }
+
+        let focus_pt = self.eye + self.focus_dist * dir;          //-- dir is 1 unit along forward, so this is on the focal plane
+        let (lx, ly) = sampler::concentric_disk(rng);
+        let origin = self.eye + (self.aperture * lx) * self.right + (self.aperture * ly) * self.cam_up;
+        Ray{origin, dir: focus_pt - origin}
+    }
+
+    fn eye(&self) -> Point{
+        self.eye
+    }
+
+    fn aspect(&self) -> f64{
+        self.aspect
     }
-    */
 
+} impl Default for Camera{
+    fn default() -> Camera{
+        Camera::new()
+    }
 } impl Stringable for Camera{
     fn stringy(&self) -> String{
-        return String::from("position: ".to_owned() + &self.pos.stringy() + ", w = " + &self.w.to_string() + ", h = "+ &self.h.to_string()); 
+        "eye: ".to_owned() + &self.eye.stringy() + ", target: " + &self.target.stringy() + ", vfov = " + &self.vfov.to_string() + ", aspect = " + &self.aspect.to_string()
+            + ", aperture = " + &self.aperture.to_string() + ", focus = " + &self.focus_dist.to_string()
+    }
+}
+
+//---- Orthographic: parallel rays along the view direction from a height x (aspect * height) plane through the eye
+//- view only uses the placement (eye, target, up) and aspect of the camera
+pub struct Orthographic{
+    pub view: Camera,
+    pub height: f64
+
+} impl CameraModel for Orthographic {
+
+    fn get_ray(&self, s: f64, t: f64, _rng: &mut Rng) -> Ray{
+        let (right, up, forward) = self.view.basis();
+        let half_h = 0.5 * self.height;
+        let half_w = self.view.aspect * half_h;
+        let origin = self.view.eye + ((2.0 * s - 1.0) * half_w) * right + ((1.0 - 2.0 * t) * half_h) * up;
+        Ray{origin, dir: forward}
+    }
+
+    fn eye(&self) -> Point{
+        self.view.eye
+    }
+
+    fn aspect(&self) -> f64{
+        self.view.aspect
+    }
+
+} impl Stringable for Orthographic{
+    fn stringy(&self) -> String{
+        "orthographic, eye: ".to_owned() + &self.view.eye.stringy() + ", target: " + &self.view.target.stringy()
+            + ", height = " + &self.height.to_string() + ", aspect = " + &self.view.aspect.to_string()
+    }
+}
+
+//---- Equirectangular: full 360 x 180 degree panorama around the eye, centered on the view direction
+//- s maps to longitude (-180 at the left edge, 180 at the right), t to latitude (90 up at the top, -90 at the bottom)
+pub struct Equirectangular{
+    pub view: Camera
+
+} impl CameraModel for Equirectangular {
+
+    fn get_ray(&self, s: f64, t: f64, _rng: &mut Rng) -> Ray{
+        let (right, up, forward) = self.view.basis();
+        let phi = (2.0 * s - 1.0) * PI;
+        let theta = (0.5 - t) * PI;
+        let dir = theta.cos() * (phi.sin() * right + phi.cos() * forward) + theta.sin() * up;
+        Ray{origin: self.view.eye, dir}
+    }
+
+    fn eye(&self) -> Point{
+        self.view.eye
+    }
+
+    //-- 360 x 180 degrees
+    fn aspect(&self) -> f64{
+        2.0
+    }
+
+} impl Stringable for Equirectangular{
+    fn stringy(&self) -> String{
+        "equirectangular, eye: ".to_owned() + &self.view.eye.stringy() + ", facing: " + &self.view.target.stringy()
     }
-}
\ No newline at end of file
+}
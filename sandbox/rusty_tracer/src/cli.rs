@@ -0,0 +1,235 @@
+// Bill Derksen - 10/26
+//-- command line options for the rusty tracer binary
+
+use std::path::PathBuf;
+use std::thread;
+
+use rusty_tracer::film::{ToneMap};
+use rusty_tracer::image::{ImageFormat};
+use rusty_tracer::integrator;
+
+pub const USAGE: &str = "\
+usage: rusty_tracer [options] [scene]
+
+arguments:
+  scene                  scene description file (default: scenes/cbox_sphere.scene)
+
+options:
+  -o, --output <path>    output image (default: output/cbox_sphere.png)
+                         .png is 8 bit, .hdr (Radiance) and .pfm (Portable FloatMap) keep the full float range
+      --width <px>       image width (default: 1920, or 16:9 of --height)
+      --height <px>      image height (default: 1080, or 16:9 of --width)
+      --samples <n>      samples per pixel, jittered over the pixel for antialiasing (default: 1)
+      --threads <n>      worker threads (default: available cores)
+      --depth <n>        max bounces per camera ray, 0 for none (default: 5)
+      --integrator <name>
+                         phong, path (Monte Carlo path tracing, use with --samples),
+                         normals, depth or ao (ambient occlusion) (default: phong)
+      --tonemap <name>   how png output squeezes bright values into range: clamp, reinhard or aces (default: clamp)
+      --exposure <stops> brighten (> 0) or darken (< 0) png output, each stop doubles or halves it (default: 0)
+  -h, --help             print this message";
+
+const MAX_DIM: u32 = 16384;
+const MAX_STOPS: f64 = 32.0;
+
+//---- Options: everything main needs to set up a render
+pub struct Options{
+    pub scene: PathBuf,
+    pub output: PathBuf,
+    pub width: u32,
+    pub height: u32,
+    pub samples: u32,
+    pub threads: usize,
+    pub max_depth: u32,
+    pub integrator: String,
+    pub tone_map: ToneMap,
+    pub exposure: f64
+}
+
+//---- Cli Error: help was asked for, or the arguments were bad
+pub enum CliError{
+    Help,
+    Invalid(String)
+}
+
+//-- parse args (without the program name)
+pub fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Options, CliError>{
+
+    let mut scene: Option<PathBuf> = None;
+    let mut output: Option<PathBuf> = None;
+    let mut width: Option<u32> = None;
+    let mut height: Option<u32> = None;
+    let mut samples: u32 = 1;
+    let mut threads: usize = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    let mut max_depth: u32 = 5;
+    let mut integrator = String::from("phong");
+    let mut tone_map = ToneMap::Clamp;
+    let mut exposure: f64 = 0.0;
+
+    while let Some(arg) = args.next() {
+
+        //-- split --flag=value, otherwise the value is the next arg
+        let (flag, inline_val) = match arg.find('=') {
+            Some(i) if arg.starts_with("--") => (arg[..i].to_string(), Some(arg[i + 1..].to_string())),
+            _ => (arg.clone(), None)
+        };
+        let mut value = |name: &str| -> Result<String, CliError>{
+            match inline_val.clone().or_else(|| args.next()) {
+                Some(v) => Ok(v),
+                None => Err(CliError::Invalid(format!("{} expects a value", name)))
+            }
+        };
+
+        match flag.as_str() {
+            "-h" | "--help" => return Err(CliError::Help),
+            "-o" | "--output" => {
+                let path = PathBuf::from(value(&flag)?);
+                if ImageFormat::from_path(&path).is_none() {
+                    return Err(CliError::Invalid(format!("{} expects a file ending in .{}, got '{}'", flag, ImageFormat::EXTENSIONS.join(", ."), path.display())));
+                }
+                output = Some(path);
+            }
+            "--width" => width = Some(positive(&flag, &value(&flag)?, MAX_DIM)?),
+            "--height" => height = Some(positive(&flag, &value(&flag)?, MAX_DIM)?),
+            "--samples" => samples = positive(&flag, &value(&flag)?, u32::MAX)?,
+            "--threads" => threads = positive(&flag, &value(&flag)?, 1024)? as usize,
+            "--depth" => max_depth = whole(&flag, &value(&flag)?, 64)?,
+            "--integrator" => {
+                integrator = value(&flag)?;
+                if !integrator::NAMES.contains(&integrator.as_str()) {
+                    return Err(CliError::Invalid(format!("{} expects one of {}, got '{}'", flag, integrator::NAMES.join(", "), integrator)));
+                }
+            }
+            "--tonemap" => {
+                let name = value(&flag)?;
+                tone_map = match ToneMap::by_name(&name) {
+                    Some(t) => t,
+                    None => return Err(CliError::Invalid(format!("{} expects one of {}, got '{}'", flag, ToneMap::NAMES.join(", "), name)))
+                };
+            }
+            "--exposure" => exposure = number(&flag, &value(&flag)?, -MAX_STOPS, MAX_STOPS)?,
+            _ if flag.starts_with('-') && flag.len() > 1 => return Err(CliError::Invalid(format!("unknown option {}", flag))),
+            _ => {
+                if scene.is_some() {
+                    return Err(CliError::Invalid(format!("unexpected argument {}, only one scene file can be given", arg)));
+                }
+                scene = Some(PathBuf::from(arg));
+            }
+        }
+    }
+
+    //-- fill in a missing dimension from a 16:9 aspect ratio
+    let (width, height) = match (width, height) {
+        (Some(w), Some(h)) => (w, h),
+        (Some(w), None) => (w, (w * 9 / 16).max(1)),
+        (None, Some(h)) => (((h as u64 * 16 / 9) as u32).clamp(1, MAX_DIM), h),
+        (None, None) => (1920, 1080)
+    };
+
+    Ok(Options{
+        scene: scene.unwrap_or_else(|| PathBuf::from("scenes/cbox_sphere.scene")),
+        output: output.unwrap_or_else(|| PathBuf::from("output/cbox_sphere.png")),
+        width, height, samples, threads, max_depth, integrator, tone_map, exposure
+    })
+}
+
+//-- parse a whole number in 1..=max
+fn positive(flag: &str, val: &str, max: u32) -> Result<u32, CliError>{
+    match val.parse::<u32>() {
+        Ok(n) if n >= 1 && n <= max => Ok(n),
+        _ => Err(CliError::Invalid(format!("{} expects a whole number from 1 to {}, got '{}'", flag, max, val)))
+    }
+}
+
+//-- parse a whole number in 0..=max
+fn whole(flag: &str, val: &str, max: u32) -> Result<u32, CliError>{
+    match val.parse::<u32>() {
+        Ok(n) if n <= max => Ok(n),
+        _ => Err(CliError::Invalid(format!("{} expects a whole number from 0 to {}, got '{}'", flag, max, val)))
+    }
+}
+
+//-- parse a number in min..=max
+fn number(flag: &str, val: &str, min: f64, max: f64) -> Result<f64, CliError>{
+    match val.parse::<f64>() {
+        Ok(n) if n >= min && n <= max => Ok(n),
+        _ => Err(CliError::Invalid(format!("{} expects a number from {} to {}, got '{}'", flag, min, max, val)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Result<Options, CliError>{
+        parse(list.iter().map(|a| a.to_string()))
+    }
+
+    //-- the message of an Invalid error, panics on anything else
+    fn invalid(list: &[&str]) -> String{
+        match args(list) {
+            Err(CliError::Invalid(msg)) => msg,
+            Err(CliError::Help) => panic!("{:?}: expected an error, got help", list),
+            Ok(_) => panic!("{:?}: expected an error, args were accepted", list)
+        }
+    }
+
+    #[test]
+    fn help(){
+        assert!(matches!(args(&["--help"]), Err(CliError::Help)));
+        assert!(matches!(args(&["scenes/a.scene", "-h"]), Err(CliError::Help)));
+    }
+
+    #[test]
+    fn defaults_without_scene_argument(){
+        let opts = args(&[]).ok().expect("no args should be fine");
+        assert_eq!(opts.scene, PathBuf::from("scenes/cbox_sphere.scene"));
+        assert_eq!(opts.output, PathBuf::from("output/cbox_sphere.png"));
+        assert_eq!((opts.width, opts.height, opts.samples, opts.max_depth), (1920, 1080, 1, 5));
+        assert_eq!(opts.tone_map, ToneMap::Clamp);
+    }
+
+    #[test]
+    fn scene_and_values(){
+        let opts = args(&["--width=640", "-o", "out/x.hdr", "my.scene", "--samples", "16", "--exposure", "-1.5"]).ok().unwrap();
+        assert_eq!(opts.scene, PathBuf::from("my.scene"));
+        assert_eq!(opts.output, PathBuf::from("out/x.hdr"));
+        assert_eq!((opts.width, opts.height, opts.samples), (640, 360, 16));
+        assert_eq!(opts.exposure, -1.5);
+    }
+
+    #[test]
+    fn unknown_flag(){
+        assert!(invalid(&["--fast"]).contains("--fast"));
+        assert!(invalid(&["--frames", "2"]).contains("--frames"));
+    }
+
+    #[test]
+    fn second_scene_argument(){
+        assert!(invalid(&["a.scene", "b.scene"]).contains("b.scene"));
+    }
+
+    #[test]
+    fn missing_value(){
+        assert!(invalid(&["--width"]).contains("expects a value"));
+    }
+
+    #[test]
+    fn zero_or_non_numeric_sizes_and_samples(){
+        for flag in ["--width", "--height", "--samples"] {
+            for val in ["0", "abc", "-3", "1.5"] {
+                let msg = invalid(&[flag, val]);
+                assert!(msg.contains(flag) && msg.contains(val), "{} {}: {}", flag, val, msg);
+            }
+        }
+        assert!(invalid(&["--width", "99999"]).contains("16384"));
+    }
+
+    #[test]
+    fn bad_names(){
+        assert!(invalid(&["--integrator", "magic"]).contains("magic"));
+        assert!(invalid(&["--tonemap", "filmic"]).contains("filmic"));
+        assert!(invalid(&["-o", "out.jpg"]).contains("out.jpg"));
+        assert!(invalid(&["--exposure", "big"]).contains("big"));
+    }
+}
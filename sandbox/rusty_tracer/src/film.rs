@@ -0,0 +1,93 @@
+// Bill Derksen - 10/26
+//-- film: the framebuffer renders land in, linear rgb until it is developed for display
+//
+//  Developing runs exposure, then a tone mapper (squeezing any brightness into [0, 1]), then sRGB
+//  encoding to 8 bit. Float image formats skip all that and store the film as is.
+
+use crate::shading::{Color};
+use crate::vmaths::{Point};
+
+//---- Film: width x height linear rgb pixels, row-major from the top left
+pub struct Film{
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<Point>
+
+} impl Film {
+
+    //-- all black
+    pub fn new(width: u32, height: u32) -> Film{
+        Film{width, height, pixels: vec![Point::default(); (width as usize) * (height as usize)]}
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> Point{
+        self.pixels[self.index(x, y)]
+    }
+
+    pub fn set(&mut self, x: u32, y: u32, c: Point){
+        let i = self.index(x, y);
+        self.pixels[i] = c;
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize{
+        (y as usize) * (self.width as usize) + (x as usize)
+    }
+
+    //-- display pixels: scaled by 2^exposure (in stops), tone mapped, then sRGB encoded
+    pub fn develop(&self, tone_map: ToneMap, exposure: f64) -> Vec<Color>{
+        let scale = 2f64.powf(exposure);
+        self.pixels.iter().map(|&c| Color::from_display(tone_map.apply(scale * c))).collect()
+    }
+}
+
+//---- Tone Map: curve taking linear radiance (any brightness) to display values in [0, 1]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ToneMap{
+    Clamp,                          //-- cut off at 1, bright areas saturate to flat white
+    Reinhard,                       //-- c / (1 + c) per channel, never quite reaches white
+    Aces                            //-- filmic S-curve (Narkowicz's fit of ACES), punchy contrast and soft highlights
+
+} impl ToneMap {
+
+    pub const NAMES: [&'static str; 3] = ["clamp", "reinhard", "aces"];
+
+    pub fn by_name(name: &str) -> Option<ToneMap>{
+        match name {
+            "clamp" => Some(ToneMap::Clamp),
+            "reinhard" => Some(ToneMap::Reinhard),
+            "aces" => Some(ToneMap::Aces),
+            _ => None
+        }
+    }
+
+    pub fn apply(&self, c: Point) -> Point{
+        let curve = match self {
+            ToneMap::Clamp => |x: f64| x,
+            ToneMap::Reinhard => |x: f64| x / (1.0 + x),
+            ToneMap::Aces => |x: f64| (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)
+        };
+
+        //- negative and nan channels (from numerical noise) go to black, infinite ones are capped so the curves
+        //- see a finite value (inf / inf would be nan) and come out white
+        let map = |x: f64| if x > 0.0 { curve(x.min(1e12)).clamp(0.0, 1.0) } else { 0.0 };
+        Point::gen(map(c.x), map(c.y), map(c.z))
+    }
+}
+
+//-- sRGB transfer curve: linear display value in [0, 1] to the encoded value monitors expect
+pub fn srgb_encode(x: f64) -> f64{
+    if x <= 0.0031308 {
+        12.92 * x
+    } else {
+        1.055 * x.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+//-- inverse of srgb_encode: an encoded value in [0, 1] (e.g. from an 8 bit image) back to linear
+pub fn srgb_decode(x: f64) -> f64{
+    if x <= 0.04045 {
+        x / 12.92
+    } else {
+        ((x + 0.055) / 1.055).powf(2.4)
+    }
+}
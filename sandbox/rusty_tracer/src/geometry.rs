@@ -1,11 +1,22 @@
 // Bill Derksen - 8/21
 //-- some hittable geometries and objects to be used in scene
 
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+use crate::aabb::{Aabb};
 use crate::stringable::{Stringable};
 use crate::hittable::{Hittable, HitInfo};
 use crate::vmaths::{Point, Vec3};
 use crate::ray::{Ray};
 use crate::material::{Material};
+use crate::sampler::{Rng};
+
+//-- thickness given to flat bounds so they can still be hit
+const RECT_PAD: f64 = 1e-4;
+
+//-- relative tolerance for the ray-triangle determinant
+const TRI_EPS: f64 = 1e-9;
 
 //---- Sphere: follows eq (x-h)^2 + (y-i)^2 + (z-j)^2 = R^2
 //-- vector form: ||x - c||^2 = R^2
@@ -15,48 +26,176 @@ pub struct Sphere{
     pub r: f64,
     pub def_color: Point,
     pub material: Material          //-- TODO: make sure this works
+} impl Sphere {
+
+    //-- (u, v) of a point on the unit sphere: u = longitude around +y starting at -x, v = latitude from -y (0) to +y (1)
+    pub fn uv(n: Vec3) -> (f64, f64){
+        let theta = (-n.y).clamp(-1.0, 1.0).acos();
+        let phi = (-n.z).atan2(n.x) + PI;
+        (phi / (2.0 * PI), theta / PI)
+    }
+
 } impl Stringable for Sphere{
 
     fn stringy(&self) -> String{
-        return String::from("center = ".to_owned() + &self.cen.stringy() + ", r = " + &self.r.to_string());
+        "center = ".to_owned() + &self.cen.stringy() + ", r = " + &self.r.to_string()
     }
 
 } impl Hittable for Sphere{     //-- Ray xXx Sphere: ||x - c||^2 = R^2, solve for t where x = P(t) 
 
-    fn hits(&self, r: &Ray) -> Option<HitInfo>{
-        let mut ray = *r;
-        ray.dir = ray.dir.unit();                   //-- NOTE: convert to unit vector for calculation... avoid extra comp?
-        
-        //println!("unit ray: {}", ray.stringy());
-
-        let discrim = (2.0 * ray.dir.dot(ray.origin - self.cen)).powf(2.0) - (4.0 * ray.dir.mag().powf(2.0)) * ((ray.origin - self.cen).mag().powf(2.0) - self.r.powf(2.0));
-        
-        //println!("discriminant: {}", discrim);
-        if discrim < 0.01 && discrim > 0.0 {                                            //-- 1x hit handling
-        
-            let t = -(ray.dir.dot(ray.origin - self.cen)) / ray.dir.mag().powf(2.0);          //- solve for t
-            //Some(HitInfo{ip: ray.at(t), norm: ray.at(t) - self.cen, obj: &self})                        //- solve for incident pt + norm
-            Some(HitInfo{ip: ray.at(t), norm: ray.at(t) - self.cen, hit_mat: &self.material}) //TODO: test
-
-        } else if discrim >= 0.01{                                                      //-- 2x hit handling
-    
-            let t = (-2.0 * (ray.dir.dot(ray.origin - self.cen)) + discrim.sqrt())  / (2.0 * ray.dir.mag().powf(2.0));   //- solve for t (want closer hit)
-            //Some(HitInfo{ip: ray.at(t), norm: ray.at(t) - self.cen, obj: &self})                                            //- solve for incident pt + norm
-            Some(HitInfo{ip: ray.at(t), norm: ray.at(t) - self.cen, hit_mat: &self.material}) //TODO: test
+    //-- ||O + tD - c||^2 = R^2  -->  (D.D)t^2 + 2(D.(O - c))t + (||O - c||^2 - R^2) = 0
+    //- nearest root in range; a ray starting inside only gets the far (exit) root, a tangent ray gets the double root
+    fn hits(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitInfo<'_>>{
+        let oc = ray.origin - self.cen;
+        let a = ray.dir.dot(ray.dir);
+        let half_b = ray.dir.dot(oc);
+        let c = oc.dot(oc) - self.r * self.r;
+
+        let discrim = half_b * half_b - a * c;
+        if discrim < 0.0 || a == 0.0 {                  //-- miss (or no direction to go in)
+            return None;
+        }
 
-        } else {                //-- miss handling
-            None
+        //- near root first, then far
+        let sqrt_d = discrim.sqrt();
+        let mut t = (-half_b - sqrt_d) / a;
+        if t <= t_min || t >= t_max {
+            t = (-half_b + sqrt_d) / a;
+            if t <= t_min || t >= t_max {
+                return None;
+            }
         }
+
+        //- dividing by r keeps it unit length, and pointing out even for hits from inside
+        let outward = (1.0 / self.r) * (ray.at(t) - self.cen);
+        let mut hit_rec = HitInfo::new(ray, t, outward, &self.material);
+        hit_rec.uv = Sphere::uv(outward);
+        Some(hit_rec)
     }
 
     fn get_pos(&self) -> Point{
         self.cen
     }
+
+    fn bounding_box(&self) -> Aabb{
+        let r = Point::gen(self.r, self.r, self.r);
+        Aabb::new(self.cen - r, self.cen + r)
+    }
+
+    fn area(&self) -> f64{
+        4.0 * PI * self.r * self.r
+    }
+
+    //-- uniform direction from the center: z uniform in [-1, 1], angle uniform around it
+    fn sample_surface(&self, rng: &mut Rng) -> Option<(Point, Vec3)>{
+        let z = 1.0 - 2.0 * rng.next_f64();
+        let ring = (1.0 - z * z).max(0.0).sqrt();
+        let phi = 2.0 * PI * rng.next_f64();
+        let n = Vec3::gen(ring * phi.cos(), ring * phi.sin(), z);
+        Some((self.cen + self.r * n, n))
+    }
 } 
 
+//---- Triangle: vertices v0, v1, v2, counter-clockwise when seen from the front
+//- optional per-vertex normals (smooth shading) and texture coords, e.g. from a mesh
+pub struct Triangle{
+    pub v0: Point,
+    pub v1: Point,
+    pub v2: Point,
+    pub normals: Option<[Vec3; 3]>,
+    pub uvs: Option<[(f64, f64); 3]>,
+    pub material: Arc<Material>     //-- shared, a mesh has one copy per material not per face
+} impl Triangle {
+
+    pub fn gen(a: Point, b: Point, c: Point) -> Triangle{
+        Triangle{v0: a, v1: b, v2: c, normals: None, uvs: None, material: Arc::new(Material::default())}
+    }
+
+    //-- unit geometric normal, (v1 - v0) x (v2 - v0)
+    pub fn normal(&self) -> Vec3{
+        (self.v1 - self.v0).cross(self.v2 - self.v0).unit()
+    }
+
+} impl Stringable for Triangle{
+
+    fn stringy(&self) -> String{
+        "v0 = ".to_owned() + &self.v0.stringy() + ", v1 = " + &self.v1.stringy() + ", v2 = " + &self.v2.stringy()
+    }
+
+} impl Hittable for Triangle {     //-- Ray xXx Triangle: Moller-Trumbore, solve O + tD = (1-u-v)v0 + u*v1 + v*v2
+
+    fn hits(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitInfo<'_>>{
+        let e1 = self.v1 - self.v0;
+        let e2 = self.v2 - self.v0;
+
+        let p = ray.dir.cross(e2);
+        let det = e1.dot(p);
+        if det.abs() < TRI_EPS * e1.mag() * e2.mag() * ray.dir.mag() {          //-- ray parallel to the triangle's plane (or degenerate triangle)
+            return None;
+        }
+        let inv_det = 1.0 / det;
+
+        let s = ray.origin - self.v0;
+        let u = s.dot(p) * inv_det;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let q = s.cross(e1);
+        let v = ray.dir.dot(q) * inv_det;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let t = e2.dot(q) * inv_det;
+        if t <= t_min || t >= t_max {
+            return None;
+        }
+
+        //-- geometric normal (or interpolated vertex normal if given), front face is the counter-clockwise side
+        let geo_n = e1.cross(e2).unit();
+        let n = match self.normals {
+            Some([n0, n1, n2]) => ((1.0 - u - v) * n0 + u * n1 + v * n2).unit(),
+            None => geo_n
+        };
+        let mut hit_rec = HitInfo::new(ray, t, n, &self.material);
+        hit_rec.geo_norm = geo_n;
+        hit_rec.front_face = ray.dir.dot(geo_n) < 0.0;
+        hit_rec.bary = (u, v);
+        hit_rec.uv = match self.uvs {
+            Some([uv0, uv1, uv2]) => (
+                (1.0 - u - v) * uv0.0 + u * uv1.0 + v * uv2.0,
+                (1.0 - u - v) * uv0.1 + u * uv1.1 + v * uv2.1),
+            None => (u, v)
+        };
+        Some(hit_rec)
+    }
+
+    fn get_pos(&self) -> Point{
+        (1.0 / 3.0) * (self.v0 + self.v1 + self.v2)
+    }
+
+    fn bounding_box(&self) -> Aabb{
+        Aabb::new(self.v0.min(self.v1).min(self.v2), self.v0.max(self.v1).max(self.v2)).padded(RECT_PAD)
+    }
+
+    fn area(&self) -> f64{
+        0.5 * (self.v1 - self.v0).cross(self.v2 - self.v0).mag()
+    }
+
+    //-- sqrt warps the first random number so points don't bunch up at v0
+    fn sample_surface(&self, rng: &mut Rng) -> Option<(Point, Vec3)>{
+        let su = rng.next_f64().sqrt();
+        let v = rng.next_f64();
+        let p = (1.0 - su) * self.v0 + (su * (1.0 - v)) * self.v1 + (su * v) * self.v2;
+        Some((p, self.normal()))
+    }
+}
+
 //-------------------- Rectangles
 
-//-- XYRect: Rectangle on the z-plane
+
+//-- XYRect: Rectangle on the z-plane, its outward normal is +z (hits from -z are back faces)
 pub struct XYRect{
     pub z: f64,
     pub x0: f64,
@@ -85,30 +224,43 @@ pub struct XYRect{
 
 } impl Hittable for XYRect {
 
-    fn hits(&self, ray: &Ray) -> Option<HitInfo> {
+    fn hits(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitInfo<'_>> {
 
         //-- equation: z given, so can solve for t P(t)z = ray.origin.z + t*ray.dir.z
         let t: f64 = (self.z - ray.origin.z) / ray.dir.z;
+        if !(t > t_min && t < t_max) {                     //-- also rejects NaN for rays parallel to the rect
+            return None;
+        }
         let x: f64 = ray.origin.x + t * ray.dir.x;
-        let y: f64= ray.origin.y + t * ray.dir.y;
+        let y: f64 = ray.origin.y + t * ray.dir.y;
 
         //-- hit if within rectangle coordinate bounds 
         if (x <= self.x1 && x >= self.x0) && ( y <= self.y1 && y >= self.y0) {
-            if ray.dir.z <= 0.0 && self.z <= 0.0 {      //-- TODO: had to add here to make this correct
-                Some(HitInfo{ip: ray.at(t), norm: Point::gen(0.0, 0.0, 1.0), hit_mat: &self.material })
-            } else {
-                Some(HitInfo{ip: ray.at(t), norm: Point::gen(0.0, 0.0, -1.0), hit_mat: &self.material }) //TODO: test
-            }
-            
+            let mut hit_rec = HitInfo::new(ray, t, Point::gen(0.0, 0.0, 1.0), &self.material);
+            hit_rec.uv = ((x - self.x0) / (self.x1 - self.x0), (y - self.y0) / (self.y1 - self.y0));
+            Some(hit_rec)
         } else { None }
     }
 
     fn get_pos(&self) -> Point {
         Point::gen(0.0, 0.0, self.z)
     }
+
+    fn bounding_box(&self) -> Aabb{
+        Aabb::new(Point::gen(self.x0, self.y0, self.z), Point::gen(self.x1, self.y1, self.z)).padded(RECT_PAD)
+    }
+
+    fn area(&self) -> f64{
+        (self.x1 - self.x0) * (self.y1 - self.y0)
+    }
+
+    fn sample_surface(&self, rng: &mut Rng) -> Option<(Point, Vec3)>{
+        let p = Point::gen(self.x0 + rng.next_f64() * (self.x1 - self.x0), self.y0 + rng.next_f64() * (self.y1 - self.y0), self.z);
+        Some((p, Vec3::gen(0.0, 0.0, 1.0)))
+    }
 }
 
-//-- XZRect: Rectangle that lies on the y-plane
+//-- XZRect: Rectangle that lies on the y-plane, its outward normal is +y
 pub struct XZRect{
     pub y: f64,
     pub x0: f64,
@@ -137,29 +289,43 @@ pub struct XZRect{
 
 } impl Hittable for XZRect {
 
-    fn hits(&self, ray: &Ray) -> Option<HitInfo> {
+    fn hits(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitInfo<'_>> {
 
         //-- equation: y given, so can solve for t P(t)y = ray.origin.y + t*ray.dir.y
         let t: f64 = (self.y - ray.origin.y) / ray.dir.y;
+        if !(t > t_min && t < t_max) {                     //-- also rejects NaN for rays parallel to the rect
+            return None;
+        }
         let x: f64 = ray.origin.x + t * ray.dir.x;
-        let z: f64= ray.origin.z + t * ray.dir.z;
+        let z: f64 = ray.origin.z + t * ray.dir.z;
 
         //-- hit if within rectangle coordinate bounds 
         if (x <= self.x1 && x >= self.x0) && ( z <= self.z1 && z >= self.z0) {
-            if ray.dir.y <=0.0{          
-                Some(HitInfo{ip: ray.at(t), norm: Point::gen(0.0, -1.0, 0.0), hit_mat: &self.material })    //-- note: currently just doing opposite of ray component for normal..     
-            } else {
-                Some(HitInfo{ip: ray.at(t), norm: Point::gen(0.0, 1.0, 0.0), hit_mat: &self.material})      
-            }
+            let mut hit_rec = HitInfo::new(ray, t, Point::gen(0.0, 1.0, 0.0), &self.material);
+            hit_rec.uv = ((x - self.x0) / (self.x1 - self.x0), (z - self.z0) / (self.z1 - self.z0));
+            Some(hit_rec)
         } else { None }
     }
 
     fn get_pos(&self) -> Point {
         Point::gen(0.0, self.y, 0.0)
     }
+
+    fn bounding_box(&self) -> Aabb{
+        Aabb::new(Point::gen(self.x0, self.y, self.z0), Point::gen(self.x1, self.y, self.z1)).padded(RECT_PAD)
+    }
+
+    fn area(&self) -> f64{
+        (self.x1 - self.x0) * (self.z1 - self.z0)
+    }
+
+    fn sample_surface(&self, rng: &mut Rng) -> Option<(Point, Vec3)>{
+        let p = Point::gen(self.x0 + rng.next_f64() * (self.x1 - self.x0), self.y, self.z0 + rng.next_f64() * (self.z1 - self.z0));
+        Some((p, Vec3::gen(0.0, 1.0, 0.0)))
+    }
 }
 
-//-- YZRect: Rectangle that lies on the x-plane
+//-- YZRect: Rectangle that lies on the x-plane, its outward normal is +x
 pub struct YZRect{
     pub x: f64,
     pub y0: f64,
@@ -188,26 +354,40 @@ pub struct YZRect{
 
 } impl Hittable for YZRect {
 
-    fn hits(&self, ray: &Ray) -> Option<HitInfo> {
+    fn hits(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitInfo<'_>> {
 
         //-- equation: x given, so can solve for t P(t)x = ray.origin.x + t*ray.dir.x
         let t: f64 = (self.x - ray.origin.x) / ray.dir.x;
+        if !(t > t_min && t < t_max) {                     //-- also rejects NaN for rays parallel to the rect
+            return None;
+        }
         let y: f64 = ray.origin.y + t * ray.dir.y;
-        let z: f64= ray.origin.z + t * ray.dir.z;
+        let z: f64 = ray.origin.z + t * ray.dir.z;
 
         //-- hit if within rectangle coordinate bounds 
         if (y <= self.y1 && y >= self.y0) && ( z <= self.z1 && z >= self.z0) {
-            if ray.dir.x <=0.0 {          
-                Some(HitInfo{ip: ray.at(t), norm: Point::gen(-1.0, 0.0, 0.0), hit_mat: &self.material})    //-- note: currently just doing opposite of ray component for normal..     
-            } else {
-                Some(HitInfo{ip: ray.at(t), norm: Point::gen(1.0, 0.0, 0.0), hit_mat: &self.material})      
-            }
+            let mut hit_rec = HitInfo::new(ray, t, Point::gen(1.0, 0.0, 0.0), &self.material);
+            hit_rec.uv = ((y - self.y0) / (self.y1 - self.y0), (z - self.z0) / (self.z1 - self.z0));
+            Some(hit_rec)
         } else { None }
     }
 
     fn get_pos(&self) -> Point {
         Point::gen(self.x, 0.0, 0.0)
     }
+
+    fn bounding_box(&self) -> Aabb{
+        Aabb::new(Point::gen(self.x, self.y0, self.z0), Point::gen(self.x, self.y1, self.z1)).padded(RECT_PAD)
+    }
+
+    fn area(&self) -> f64{
+        (self.y1 - self.y0) * (self.z1 - self.z0)
+    }
+
+    fn sample_surface(&self, rng: &mut Rng) -> Option<(Point, Vec3)>{
+        let p = Point::gen(self.x, self.y0 + rng.next_f64() * (self.y1 - self.y0), self.z0 + rng.next_f64() * (self.z1 - self.z0));
+        Some((p, Vec3::gen(1.0, 0.0, 0.0)))
+    }
 }
 
 //---- AABox: composed of 6 rects, 2 parallel for each plane
@@ -217,37 +397,57 @@ pub struct AABox{
     pub sides: Vec<Box<dyn Hittable>>   //-- TODO: only allow recs to be contained in here?
 } impl AABox {
 
-    //-- generate sides and init sides vec
-    pub fn gen(min_corner: Point, max_corner: Point) -> AABox {
-
-        let mut sides_v: Vec<Box<dyn Hittable>> = Vec::new();
-        sides_v.push(Box::new(YZRect::gen(min_corner.x, min_corner.y, max_corner.y, min_corner.z, max_corner.z)));  //-- left
-        sides_v.push(Box::new(YZRect::gen(max_corner.x, min_corner.y, max_corner.y, min_corner.z, max_corner.z)));  //-- right
-        sides_v.push(Box::new(XYRect::gen(min_corner.z, min_corner.x, max_corner.x, min_corner.y, max_corner.y)));  //-- front
-        sides_v.push(Box::new(XYRect::gen(max_corner.z, min_corner.x, max_corner.x, min_corner.y, max_corner.y)));  //-- back
-        sides_v.push(Box::new(XYRect::gen(max_corner.y, min_corner.x, max_corner.x, min_corner.z, max_corner.z)));  //-- top
-        sides_v.push(Box::new(XYRect::gen(min_corner.y, min_corner.x, max_corner.x, min_corner.z, max_corner.z)));  //-- bottom
+    //-- generate sides and init sides vec, every side gets the box's material
+    pub fn gen(min_corner: Point, max_corner: Point, material: Material) -> AABox {
+
+        let (lo, hi) = (min_corner.min(max_corner), min_corner.max(max_corner));
+        let sides_v: Vec<Box<dyn Hittable>> = vec![
+            Box::new(YZRect{material: material.clone(), ..YZRect::gen(lo.x, lo.y, hi.y, lo.z, hi.z)}),  //-- left
+            Box::new(YZRect{material: material.clone(), ..YZRect::gen(hi.x, lo.y, hi.y, lo.z, hi.z)}),  //-- right
+            Box::new(XYRect{material: material.clone(), ..XYRect::gen(lo.z, lo.x, hi.x, lo.y, hi.y)}),  //-- front
+            Box::new(XYRect{material: material.clone(), ..XYRect::gen(hi.z, lo.x, hi.x, lo.y, hi.y)}),  //-- back
+            Box::new(XZRect{material: material.clone(), ..XZRect::gen(hi.y, lo.x, hi.x, lo.z, hi.z)}),  //-- top
+            Box::new(XZRect{material, ..XZRect::gen(lo.y, lo.x, hi.x, lo.z, hi.z)})                     //-- bottom
+        ];
     
-        AABox{min_extent: min_corner, max_extent: max_corner, sides: sides_v}
+        AABox{min_extent: lo, max_extent: hi, sides: sides_v}
     }
 
 } impl Hittable for AABox {
 
-    //-- iterate over surfaces checking for hit
-    fn hits(&self, ray: &Ray) -> Option<HitInfo> {
+    //-- closest side, with its normal turned to point out of the box (rects all face +axis)
+    fn hits(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitInfo<'_>> {
+        let mut closest: Option<HitInfo> = None;
+        let mut closest_t = t_max;
         for side in &self.sides{
-            match side.hits(&ray) {
-                Some(hit_rec) => {
-                    return Some(hit_rec);
-                }
-                None => {}
+            if let Some(hit_rec) = side.hits(ray, t_min, closest_t) {
+                closest_t = hit_rec.t;
+                closest = Some(hit_rec);
             }
         }
-        None
+
+        let mut hit_rec = closest?;
+        if hit_rec.norm.dot(hit_rec.ip - self.get_pos()) < 0.0 {
+            hit_rec.norm = -hit_rec.norm;
+            hit_rec.front_face = !hit_rec.front_face;
+        }
+        Some(hit_rec)
     }
 
     fn get_pos(&self) -> Point{
-        Point::new()
+        0.5 * (self.min_extent + self.max_extent)
+    }
+
+    fn bounding_box(&self) -> Aabb{
+        Aabb::new(self.min_extent, self.max_extent).padded(RECT_PAD)
+    }
+
+    fn area(&self) -> f64{
+        box_area(self.min_extent, self.max_extent)
+    }
+
+    fn sample_surface(&self, rng: &mut Rng) -> Option<(Point, Vec3)>{
+        Some(sample_box(self.min_extent, self.max_extent, rng))
     }
 
 }
@@ -274,54 +474,114 @@ pub struct BBox{
 } impl Hittable for BBox {
 
     //-- AABB SLAB method... box is the intersection of 3 slabs (section between box side planes, x slab, y slab, z slab)
-    fn hits(&self, ray: &Ray) -> Option<HitInfo> {
+    //- the ray enters the box through the face of the slab it enters last, and leaves by the one it leaves first
+    fn hits(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitInfo<'_>> {
         
         let mut tmin = - f64::INFINITY;
         let mut tmax = f64::INFINITY;
+        let mut enter_axis = 0;
+        let mut exit_axis = 0;
+
+        for axis in 0..3 {
+            let (o, d) = (ray.origin[axis], ray.dir[axis]);
+            let (lo, hi) = (self.min_extent[axis], self.max_extent[axis]);
+
+            //-- parallel to this slab: inside it everywhere or nowhere
+            if d == 0.0 {
+                if o < lo || o > hi {
+                    return None;
+                }
+                continue;
+            }
 
-        //-- x-axis slab
-        if ray.dir.x as i64 != 0 {
-            let mut tx1: f64 = (&self.min_extent.x - ray.origin.x) / ray.dir.x;
-            let mut tx2: f64 = (&self.max_extent.x - ray.origin.x) / ray.dir.x;
-            tmin = tmin.max(tx1.min(tx2));
-            tmax = tmax.min(tx1.max(tx2));
+            let t1: f64 = (lo - o) / d;
+            let t2: f64 = (hi - o) / d;
+            if t1.min(t2) > tmin {
+                tmin = t1.min(t2);
+                enter_axis = axis;
+            }
+            if t1.max(t2) < tmax {
+                tmax = t1.max(t2);
+                exit_axis = axis;
+            }
         }
 
-        //-- y-axis slab
-        if ray.dir.y as i64 != 0 {
-            let mut ty1: f64 = (&self.min_extent.y - ray.origin.y) / ray.dir.y;
-            let mut ty2: f64 = (&self.max_extent.y - ray.origin.y) / ray.dir.y;
-            tmin = tmin.max(ty1.min(ty2));
-            tmax = tmax.min(ty1.max(ty2));
+        if tmax < tmin {
+            return None;
         }
 
-        //-- z-axis slab
-        if ray.dir.z as i64 != 0 {
-            let mut tz1: f64 = (&self.min_extent.z - ray.origin.z) / ray.dir.z;
-            let mut tz2: f64 = (&self.max_extent.z - ray.origin.z) / ray.dir.z;
-            tmin = tmin.max(tz1.min(tz2));
-            tmax = tmax.min(tz1.max(tz2));
+        //-- entry point, or the exit point if the ray starts inside
+        //- outward normal of that face: against the ray on entry, along it on exit
+        let (t, axis, sign) = if tmin > t_min { (tmin, enter_axis, -1.0) } else { (tmax, exit_axis, 1.0) };
+        if t <= t_min || t >= t_max {
+            return None;
+        }
+        let mut normal = Vec3::default();
+        let n = sign * ray.dir[axis].signum();
+        match axis {
+            0 => normal.x = n,
+            1 => normal.y = n,
+            _ => normal.z = n
         }
+        let mut hit_rec = HitInfo::new(ray, t, normal, &self.material);
+
+        //- uv across the face, from the other two axes in x, y, z order
+        let (a, b) = match axis { 0 => (1, 2), 1 => (0, 2), _ => (0, 1) };
+        let rel = hit_rec.ip - self.min_extent;
+        let size = self.max_extent - self.min_extent;
+        hit_rec.uv = ((rel[a] / size[a]).clamp(0.0, 1.0), (rel[b] / size[b]).clamp(0.0, 1.0));
+        Some(hit_rec)
+    }
 
-        //-- hit, need to calculate normal for shading
-        //TODO: FIX
-        if tmax >= tmin {
-            
-            let hit_pt = ray.at(tmin);
-            let mut normal: Vec3 = Vec3::new();
+    fn get_pos(&self) -> Point {
+        self.cen
+    }
 
-            let normal = Point::gen(0.0,0.0,0.0);
-            Some(HitInfo{ ip: hit_pt, norm: normal, hit_mat: &self.material})
+    fn bounding_box(&self) -> Aabb{
+        Aabb::new(self.min_extent, self.max_extent).padded(RECT_PAD)
+    }
 
-        } else { None }
+    fn area(&self) -> f64{
+        box_area(self.min_extent, self.max_extent)
     }
 
-    fn get_pos(&self) -> Point {
-        self.cen
+    fn sample_surface(&self, rng: &mut Rng) -> Option<(Point, Vec3)>{
+        Some(sample_box(self.min_extent, self.max_extent, rng))
     }
 
 } impl Stringable for BBox{
     fn stringy(&self) -> String{
-        return String::from("center: ".to_owned() + &self.cen.stringy() + "\nWxHxD = " + &self.w.to_string() + " x " + &self.h.to_string() + " x " + &self.d.to_string() + "\nmin extent: " + &self.min_extent.stringy() + "\nmax extent: " + &self.max_extent.stringy());
+        "center: ".to_owned() + &self.cen.stringy() + "\nWxHxD = " + &self.w.to_string() + " x " + &self.h.to_string() + " x " + &self.d.to_string() + "\nmin extent: " + &self.min_extent.stringy() + "\nmax extent: " + &self.max_extent.stringy()
     }
-}
\ No newline at end of file
+}
+
+//-- total area of the 6 faces of the box lo..hi
+fn box_area(lo: Point, hi: Point) -> f64{
+    let e = hi - lo;
+    2.0 * (e.x * e.y + e.y * e.z + e.z * e.x)
+}
+
+//-- uniform point on the surface of the box lo..hi, with its outward normal: a face picked by area, then a point on it
+fn sample_box(lo: Point, hi: Point, rng: &mut Rng) -> (Point, Vec3){
+    let e = hi - lo;
+    let faces = [e.y * e.z, e.x * e.z, e.x * e.y];                 //-- one face's area, for each axis it faces along
+    let mut pick = rng.next_f64() * (faces[0] + faces[1] + faces[2]);
+    let mut axis = 2;
+    for (i, a) in faces.iter().enumerate() {
+        if pick < *a {
+            axis = i;
+            break;
+        }
+        pick -= a;
+    }
+
+    let (a, b) = match axis { 0 => (1, 2), 1 => (0, 2), _ => (0, 1) };
+    let high_side = rng.next_f64() < 0.5;
+    let mut p = [0.0; 3];
+    let mut n = [0.0; 3];
+    p[axis] = if high_side { hi[axis] } else { lo[axis] };
+    n[axis] = if high_side { 1.0 } else { -1.0 };
+    p[a] = lo[a] + rng.next_f64() * e[a];
+    p[b] = lo[b] + rng.next_f64() * e[b];
+    (Point::gen(p[0], p[1], p[2]), Vec3::gen(n[0], n[1], n[2]))
+}
@@ -1,31 +1,94 @@
 // Bill Derksen - 8/21
 //-- hittable trait implemented by geometries
 
+use std::sync::Arc;
+
+use crate::aabb::{Aabb};
 use crate::ray::{Ray};
+use crate::sampler::{Rng};
 use crate::vmaths::{Point, Vec3};
-use crate::geometry::{Sphere};
 use crate::material::{Material};
 
-pub trait Hittable{
-    fn hits(&self, ray: &Ray) -> Option<HitInfo>;
+//---- Hittable: anything a ray can hit
+//- t is measured in units of ray.dir (P(t) = O + tD), only hits with t_min < t < t_max count
+pub trait Hittable: Send + Sync{
+
+    //-- closest hit in (t_min, t_max)
+    fn hits(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitInfo<'_>>;
     fn get_pos(&self) -> Point;
-    //fn get_material(&self) -> &Material;
+    fn bounding_box(&self) -> Aabb;
+
+    //-- any hit in (t_min, t_max), for shadow rays
+    //- containers (e.g. bvh) override this to stop at the first hit instead of finding the closest
+    fn hits_any(&self, ray: &Ray, t_min: f64, t_max: f64) -> bool{
+        self.hits(ray, t_min, t_max).is_some()
+    }
+
+    //-- surface area, 0 for shapes that can't be sampled (so can't act as lights)
+    fn area(&self) -> f64{
+        0.0
+    }
+
+    //-- uniform random point on the surface and the unit outward normal there, for sampling glowing shapes as lights
+    fn sample_surface(&self, _rng: &mut Rng) -> Option<(Point, Vec3)>{
+        None
+    }
 }
 
-#[derive(Copy, Clone)]
-pub struct HitInfo<'a>{ 
-    pub ip: Point,
-    pub norm: Vec3,
-    pub hit_mat: &'a Material //TODO: add material to be populated on hit
+//-- shared objects hit like the object itself, e.g. a glowing shape kept by both the scene and its light
+impl<T: Hittable + ?Sized> Hittable for Arc<T> {
+    fn hits(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitInfo<'_>>{
+        (**self).hits(ray, t_min, t_max)
+    }
+
+    fn get_pos(&self) -> Point{
+        (**self).get_pos()
+    }
+
+    fn bounding_box(&self) -> Aabb{
+        (**self).bounding_box()
+    }
+
+    fn hits_any(&self, ray: &Ray, t_min: f64, t_max: f64) -> bool{
+        (**self).hits_any(ray, t_min, t_max)
+    }
+
+    fn area(&self) -> f64{
+        (**self).area()
+    }
+
+    fn sample_surface(&self, rng: &mut Rng) -> Option<(Point, Vec3)>{
+        (**self).sample_surface(rng)
+    }
 }
 
 //---- hit info: returned with a ray hit/intersection
-/*
 #[derive(Copy, Clone)]
 pub struct HitInfo<'a>{ 
     pub ip: Point,
-    pub norm: Vec3,
-    pub obj: &'a Sphere     //TODO: change to Hittable
-}
-*/
+    pub t: f64,
+    pub norm: Vec3,             //-- unit normal pointing out of the surface (+axis for rects), whichever side was hit
+                                //-  this is the shading normal: interpolated from triangle vertex normals, tilted by bump maps
+    pub geo_norm: Vec3,         //-- unit normal of the actual surface, same side as norm. Equals norm unless the shape smooths it
+    pub front_face: bool,       //-- ray came from the side norm points to
+    pub hit_mat: &'a Material,
+    pub bary: (f64, f64),       //-- barycentric (u, v) of the hit on a triangle, weight of v0 is 1 - u - v. (0, 0) for other shapes
+    pub uv: (f64, f64)          //-- texture coords of the hit, each in [0, 1] across the surface
 
+} impl<'a> HitInfo<'a> {
+
+    //-- hit at ray.at(t), outward must be unit length
+    pub fn new(ray: &Ray, t: f64, outward: Vec3, hit_mat: &'a Material) -> HitInfo<'a>{
+        HitInfo{ip: ray.at(t), t, norm: outward, geo_norm: outward, front_face: ray.dir.dot(outward) < 0.0, hit_mat, bary: (0.0, 0.0), uv: (0.0, 0.0)}
+    }
+
+    //-- surface color of the hit material here
+    pub fn color(&self) -> Point{
+        self.hit_mat.color_at(self.uv, self.ip)
+    }
+
+    //-- unit normal on the side the ray came from, for shading
+    pub fn facing_norm(&self) -> Vec3{
+        if self.front_face { self.norm } else { -self.norm }
+    }
+}
@@ -0,0 +1,98 @@
+// Bill Derksen - 10/26
+//-- image files: rendered film developed to 8-bit png, or stored linear as float Radiance .hdr / PFM for later grading
+
+use std::io::{self, Write};
+use std::path::{Path};
+
+use crate::film::{Film, ToneMap};
+use crate::vmaths::{Point};
+
+//---- Image Format: picked from the output file's extension
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ImageFormat{
+    Png,                            //-- 8 bit sRGB, exposed and tone mapped
+    Hdr,                            //-- Radiance rgbe, 8 bit mantissas sharing an exponent
+    Pfm                             //-- Portable FloatMap, 32 bit float per channel
+
+} impl ImageFormat {
+
+    pub const EXTENSIONS: [&'static str; 3] = ["png", "hdr", "pfm"];
+
+    //-- None for a missing or unknown extension (case is ignored)
+    pub fn from_path(path: &Path) -> Option<ImageFormat>{
+        let ext = path.extension()?.to_string_lossy().to_ascii_lowercase();
+        match ext.as_str() {
+            "png" => Some(ImageFormat::Png),
+            "hdr" => Some(ImageFormat::Hdr),
+            "pfm" => Some(ImageFormat::Pfm),
+            _ => None
+        }
+    }
+}
+
+//-- write film to w in the given format; tone_map and exposure (in stops) only apply to png, float formats stay linear
+pub fn write<W: Write>(w: W, format: ImageFormat, film: &Film, tone_map: ToneMap, exposure: f64) -> io::Result<()>{
+    match format {
+        ImageFormat::Png => write_png(w, film, tone_map, exposure),
+        ImageFormat::Hdr => write_hdr(w, film.width, film.height, &film.pixels),
+        ImageFormat::Pfm => write_pfm(w, film.width, film.height, &film.pixels)
+    }
+}
+
+//-- 8 bit sRGB png of the developed film (see Film::develop)
+pub fn write_png<W: Write>(w: W, film: &Film, tone_map: ToneMap, exposure: f64) -> io::Result<()>{
+    let mut encoder = png::Encoder::new(w, film.width, film.height);
+    encoder.set_color(png::ColorType::RGB);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+
+    let mut data = Vec::with_capacity(film.pixels.len() * 3);
+    for c in film.develop(tone_map, exposure) {
+        data.extend_from_slice(&[c.r, c.g, c.b]);
+    }
+    writer.write_image_data(&data)?;
+    Ok(())
+}
+
+//-- Radiance .hdr: text header, then uncompressed rgbe scanlines from the top row down
+pub fn write_hdr<W: Write>(mut w: W, width: u32, height: u32, pixels: &[Point]) -> io::Result<()>{
+    write!(w, "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n", height, width)?;
+    for p in pixels {
+        w.write_all(&rgbe(*p))?;
+    }
+    Ok(())
+}
+
+//-- one pixel as rgbe: each channel's mantissa over 256, scaled by 2^(e - 128) for the brightest channel's exponent e
+pub fn rgbe(p: Point) -> [u8; 4]{
+    let (r, g, b) = (finite(p.x), finite(p.y), finite(p.z));
+    let v = r.max(g).max(b);
+    if v < 1e-32 {
+        return [0; 4];
+    }
+
+    //- v = m * 2^e with m in [0.5, 1), so every channel * 256 / 2^e fits a byte
+    let e = (v.log2().floor() as i32 + 1).min(127);
+    let scale = 256.0 / 2f64.powi(e);
+    let byte = |c: f64| (c * scale).min(255.0) as u8;
+    [byte(r), byte(g), byte(b), (e + 128) as u8]
+}
+
+//-- Portable FloatMap: text header, then little endian f32 rgb scanlines from the bottom row up
+pub fn write_pfm<W: Write>(mut w: W, width: u32, height: u32, pixels: &[Point]) -> io::Result<()>{
+    write!(w, "PF\n{} {}\n-1.0\n", width, height)?;
+    let row_len = width as usize;
+    for row in pixels.chunks(row_len.max(1)).rev() {
+        for p in row {
+            for c in [p.x, p.y, p.z] {
+                w.write_all(&(c as f32).to_le_bytes())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+//-- negative and nan channels carry no light, rgbe can't store them
+fn finite(c: f64) -> f64{
+    if c > 0.0 { c.min(f64::MAX) } else { 0.0 }
+}
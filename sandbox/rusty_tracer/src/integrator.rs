@@ -0,0 +1,144 @@
+// Bill Derksen - 10/26
+//-- integrators: strategies for turning a camera ray into a color, swappable at render time
+//
+//  The renderer only sees `dyn Integrator`, so a new strategy is just a new impl passed to
+//  render::render. The built-in ones can be looked up by name with `by_name` (see NAMES).
+
+use crate::hittable::{HitInfo};
+use crate::pathtracer::{trace_path};
+use crate::ray::{Ray};
+use crate::sampler::{self, Rng};
+use crate::scene::{Scene};
+use crate::shading::{closest_hit, trace_ray, SHADOW_EPS};
+use crate::vmaths::{Point, Vec3};
+
+//-- names accepted by by_name, in the order they are listed in help text
+pub const NAMES: [&str; 5] = ["phong", "path", "normals", "depth", "ao"];
+
+//---- Integrator: linear rgb light arriving along a camera ray
+//- called from several render threads at once; rng is seeded per pixel, so use it for any randomness
+pub trait Integrator: Send + Sync{
+    fn radiance(&self, ray: &Ray, scene: &Scene, rng: &mut Rng) -> Point;
+}
+
+//-- a built-in integrator by name, fitted to the scene where it needs a sense of scale
+pub fn by_name(name: &str, scene: &Scene, max_depth: u32) -> Option<Box<dyn Integrator>>{
+    match name {
+        "phong" => Some(Box::new(Phong{max_depth})),
+        "path" => Some(Box::new(PathTracer{max_depth})),
+        "normals" => Some(Box::new(Normals)),
+        "depth" => Some(Box::new(Depth::fit(scene))),
+        "ao" => Some(Box::new(AmbientOcclusion::fit(scene))),
+        _ => None
+    }
+}
+
+//---- Phong: direct Phong lighting with recursive mirror/glass bounces, deterministic unless there are area lights
+pub struct Phong{
+    pub max_depth: u32
+
+} impl Integrator for Phong {
+    fn radiance(&self, ray: &Ray, scene: &Scene, rng: &mut Rng) -> Point{
+        trace_ray(ray, scene, rng, 0, self.max_depth)
+    }
+}
+
+//---- Path Tracer: Monte Carlo global illumination (see pathtracer.rs), noisy at low sample counts
+pub struct PathTracer{
+    pub max_depth: u32
+
+} impl Integrator for PathTracer {
+    fn radiance(&self, ray: &Ray, scene: &Scene, rng: &mut Rng) -> Point{
+        trace_path(ray, scene, rng, self.max_depth)
+    }
+}
+
+//---- Normals: unit outward surface normal mapped from [-1, 1] to [0, 1] per axis, black on a miss
+pub struct Normals;
+
+impl Integrator for Normals {
+    fn radiance(&self, ray: &Ray, scene: &Scene, _rng: &mut Rng) -> Point{
+        match closest_hit(ray, &scene.objects) {
+            Some(hit_rec) => 0.5 * (hit_rec.norm + Point::gen(1.0, 1.0, 1.0)),
+            None => Point::default()
+        }
+    }
+}
+
+//---- Depth: distance to the closest hit as gray, white at near fading to black at far (and on a miss)
+pub struct Depth{
+    pub near: f64,
+    pub far: f64
+
+} impl Depth {
+
+    //-- near/far from the closest and furthest points of the scene bounds, seen from the camera
+    pub fn fit(scene: &Scene) -> Depth{
+        let bounds = scene.bounds();
+        let eye = scene.camera.eye();
+        let near = (eye.max(bounds.min).min(bounds.max) - eye).mag();
+        let mut far = near;
+        for i in 0..8 {
+            let corner = Point::gen(
+                if i & 1 == 0 { bounds.min.x } else { bounds.max.x },
+                if i & 2 == 0 { bounds.min.y } else { bounds.max.y },
+                if i & 4 == 0 { bounds.min.z } else { bounds.max.z });
+            far = far.max((corner - eye).mag());
+        }
+        if !far.is_finite() {
+            return Depth{near: 0.0, far: 1.0};                  //-- empty scene
+        }
+        Depth{near, far}
+    }
+
+} impl Integrator for Depth {
+    fn radiance(&self, ray: &Ray, scene: &Scene, _rng: &mut Rng) -> Point{
+        let hit_rec = match closest_hit(ray, &scene.objects) {
+            Some(hit_rec) => hit_rec,
+            None => return Point::default()
+        };
+        let dist = hit_rec.t * ray.dir.mag();
+        let span = (self.far - self.near).max(1e-9);
+        let v = (1.0 - (dist - self.near) / span).clamp(0.0, 1.0);
+        Point::gen(v, v, v)
+    }
+}
+
+//---- Ambient Occlusion: fraction of cosine weighted rays from the hit that escape within radius, as gray
+pub struct AmbientOcclusion{
+    pub samples: u32,
+    pub radius: f64
+
+} impl AmbientOcclusion {
+
+    const SAMPLES: u32 = 16;
+
+    //-- radius of a tenth of the scene's size
+    pub fn fit(scene: &Scene) -> AmbientOcclusion{
+        let size = scene.bounds().extent().mag();
+        let radius = if size.is_finite() && size > 0.0 { 0.1 * size } else { 1.0 };
+        AmbientOcclusion{samples: AmbientOcclusion::SAMPLES, radius}
+    }
+
+    fn occlusion(&self, hit_rec: &HitInfo, scene: &Scene, rng: &mut Rng) -> f64{
+        let n: Vec3 = hit_rec.facing_norm();
+        let origin = hit_rec.ip + SHADOW_EPS * n;
+        let samples = self.samples.max(1);
+        let blocked = (0..samples).filter(|_| {
+            let probe = Ray{origin, dir: self.radius * sampler::cosine_hemisphere(n, rng)};
+            scene.objects.iter().any(|obj| obj.hits_any(&probe, 0.0, 1.0))
+        }).count();
+        blocked as f64 / samples as f64
+    }
+
+} impl Integrator for AmbientOcclusion {
+    fn radiance(&self, ray: &Ray, scene: &Scene, rng: &mut Rng) -> Point{
+        match closest_hit(ray, &scene.objects) {
+            Some(hit_rec) => {
+                let v = 1.0 - self.occlusion(&hit_rec, scene, rng);
+                Point::gen(v, v, v)
+            }
+            None => Point::default()
+        }
+    }
+}
@@ -0,0 +1,24 @@
+// Bill Derksen - 10/26
+//-- rusty tracer library: geometry, shading, and scene loading used by the renderer binary
+
+pub mod aabb;
+pub mod bvh;
+pub mod hittable;
+pub mod stringable;
+pub mod vmaths;
+pub mod camera;
+pub mod geometry;
+pub mod ray;
+pub mod material;
+pub mod texture;
+pub mod noise;
+pub mod light;
+pub mod obj;
+pub mod scene;
+pub mod shading;
+pub mod sampler;
+pub mod pathtracer;
+pub mod integrator;
+pub mod render;
+pub mod film;
+pub mod image;
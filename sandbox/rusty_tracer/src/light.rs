@@ -0,0 +1,289 @@
+// Bill Derksen - 10/26
+//-- light sources placed in scene
+//
+//  Every light answers two questions for a point being lit: what light arrives there and from where
+//  (sample_li), and whether anything blocks it (occluded). id / radiance drive the path tracer and
+//  Phong's diffuse term, is drives Phong's specular term. Glowing scene geometry is wrapped up as a
+//  GeometryLight, so it lights the scene the same way.
+
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+use crate::geometry::{XZRect};
+use crate::hittable::{Hittable};
+use crate::sampler::{self, Rng};
+use crate::shading::{in_shadow, SHADOW_EPS};
+use crate::stringable::{Stringable};
+use crate::vmaths::{Point, Vec3};
+
+//---- Light: anything that illuminates the scene
+//- none of them are drawn: camera and bounce rays only ever see geometry (which can glow, see GeometryLight)
+pub trait Light: Send + Sync + Stringable{
+
+    //-- light arriving at p, None if p gets none from this light (behind it, outside its cone, ...)
+    fn sample_li(&self, p: Point, rng: &mut Rng) -> Option<LightSample>;
+
+    //-- true if light arrives from a single direction (point-like lights), so one sample says it all
+    fn is_delta(&self) -> bool{
+        true
+    }
+
+    //-- shadow ray: true if anything sits between p (on a surface with unit normal n) and the sampled light point
+    fn occluded(&self, p: Point, n: Vec3, sample: &LightSample, hit_scene: &[Box<dyn Hittable>]) -> bool{
+        in_shadow(p, n, sample.dir, sample.dist, hit_scene)
+    }
+}
+
+//---- Light Sample: one direction light arrives at a point from
+//- estimate light at the point with li * cos / pdf; delta lights (point, directional, spot) have pdf 1
+pub struct LightSample{
+    pub dir: Vec3,                  //-- unit, from the lit point toward the light
+    pub dist: f64,                  //-- to the light along dir, infinite for directional lights
+    pub li: Point,                  //-- incident radiance (intensity / dist^2 for point-like lights)
+    pub spec: Point,                //-- same, for Phong's specular term
+    pub pdf: f64                    //-- density of dir per unit solid angle at the lit point
+}
+
+//---- Point Light: emits equally in all directions from pos, falling off with the inverse square of distance
+//- id = diffuse intensity, is = specular intensity
+#[derive(Copy, Clone)]
+pub struct PointLight{
+    pub pos: Point,
+    pub id: Point,
+    pub is: Point
+
+} impl PointLight {
+
+    pub fn new() -> PointLight{
+        PointLight{pos: Point::default(), id: Point{x:1.0,y:1.0,z:1.0}, is: Point{x:1.0,y:1.0,z:1.0}}
+    }
+} impl Default for PointLight {
+    fn default() -> PointLight{
+        PointLight::new()
+    }
+} impl Light for PointLight {
+    fn sample_li(&self, p: Point, _rng: &mut Rng) -> Option<LightSample>{
+        point_sample(self.pos - p, self.id, self.is)
+    }
+} impl Stringable for PointLight {
+    fn stringy(&self) -> String{
+        "point light at ".to_owned() + &self.pos.stringy()
+    }
+}
+
+//-- delta light seen along to_light with intensities id / is, inverse square falloff
+fn point_sample(to_light: Vec3, id: Point, is: Point) -> Option<LightSample>{
+    let dist2 = to_light.dot(to_light);
+    if dist2 <= 0.0 {
+        return None;
+    }
+    let dist = dist2.sqrt();
+    Some(LightSample{dir: (1.0 / dist) * to_light, dist, li: (1.0 / dist2) * id, spec: (1.0 / dist2) * is, pdf: 1.0})
+}
+
+//---- Directional Light: parallel light from very far away (the sun), no falloff
+//- dir is the way the light travels, id / is are irradiance on a surface facing it
+#[derive(Copy, Clone)]
+pub struct DirectionalLight{
+    pub dir: Vec3,
+    pub id: Point,
+    pub is: Point
+
+} impl Light for DirectionalLight {
+    fn sample_li(&self, _p: Point, _rng: &mut Rng) -> Option<LightSample>{
+        if self.dir.mag() <= 0.0 {
+            return None;
+        }
+        Some(LightSample{dir: -self.dir.unit(), dist: f64::INFINITY, li: self.id, spec: self.is, pdf: 1.0})
+    }
+} impl Stringable for DirectionalLight {
+    fn stringy(&self) -> String{
+        "directional light along ".to_owned() + &self.dir.stringy()
+    }
+}
+
+//---- Spot Light: a point light aimed along dir, lighting only a cone around it
+//- full intensity within inner degrees of dir, easing smoothly to nothing at angle degrees
+#[derive(Copy, Clone)]
+pub struct SpotLight{
+    pub pos: Point,
+    pub dir: Vec3,
+    pub angle: f64,
+    pub inner: f64,
+    pub id: Point,
+    pub is: Point
+
+} impl SpotLight {
+
+    //-- fraction of the light leaving in unit direction d
+    pub fn falloff(&self, d: Vec3) -> f64{
+        let cos_outer = self.angle.to_radians().cos();
+        let cos_inner = self.inner.min(self.angle).to_radians().cos();
+        let cos = d.dot(self.dir.unit());
+        if cos <= cos_outer {
+            return 0.0;
+        }
+        if cos >= cos_inner {
+            return 1.0;
+        }
+
+        //- smoothstep across the edge band
+        let t = (cos - cos_outer) / (cos_inner - cos_outer);
+        t * t * (3.0 - 2.0 * t)
+    }
+
+} impl Light for SpotLight {
+    fn sample_li(&self, p: Point, _rng: &mut Rng) -> Option<LightSample>{
+        let mut sample = point_sample(self.pos - p, self.id, self.is)?;
+        let k = self.falloff(-sample.dir);
+        if k <= 0.0 {
+            return None;
+        }
+        sample.li = k * sample.li;
+        sample.spec = k * sample.spec;
+        Some(sample)
+    }
+} impl Stringable for SpotLight {
+    fn stringy(&self) -> String{
+        "spot light at ".to_owned() + &self.pos.stringy() + " aimed along " + &self.dir.stringy() + ", angle = " + &self.angle.to_string()
+    }
+}
+
+//---- Rect Light: an xz rect emitting radiance from one face, the -y one if down (a ceiling panel)
+//- an area light, so its shadows have soft edges. is = radiance for Phong's specular term
+pub struct RectLight{
+    pub rect: XZRect,
+    pub radiance: Point,
+    pub is: Point,
+    pub down: bool
+
+} impl Light for RectLight {
+
+    fn is_delta(&self) -> bool{
+        false
+    }
+
+    //-- uniform over the rect area, pdf 1 / A converted to solid angle at p
+    fn sample_li(&self, p: Point, rng: &mut Rng) -> Option<LightSample>{
+        let rect = &self.rect;
+        let pos = Point::gen(rect.x0 + rng.next_f64() * (rect.x1 - rect.x0), rect.y,
+            rect.z0 + rng.next_f64() * (rect.z1 - rect.z0));
+        let to_light = pos - p;
+        if (to_light.y > 0.0) != self.down {
+            return None;                                    //-- p is behind the lit face
+        }
+        let dist2 = to_light.dot(to_light);
+        let dist = dist2.sqrt();
+        let cos_l = to_light.y.abs() / dist;
+        let area = (rect.x1 - rect.x0) * (rect.z1 - rect.z0);
+        if cos_l <= 0.0 || area <= 0.0 {
+            return None;
+        }
+        Some(LightSample{dir: (1.0 / dist) * to_light, dist, li: self.radiance, spec: self.is, pdf: dist2 / (area * cos_l)})
+    }
+} impl Stringable for RectLight {
+    fn stringy(&self) -> String{
+        let rect = &self.rect;
+        format!("rect light at y {} over x {}..{}, z {}..{}, facing {}", rect.y, rect.x0, rect.x1, rect.z0, rect.z1,
+            if self.down { "down" } else { "up" })
+    }
+}
+
+//---- Sphere Light: a ball emitting radiance from its whole surface, an area light
+//- is = radiance for Phong's specular term
+pub struct SphereLight{
+    pub cen: Point,
+    pub r: f64,
+    pub radiance: Point,
+    pub is: Point
+
+} impl Light for SphereLight {
+
+    fn is_delta(&self) -> bool{
+        false
+    }
+
+    //-- uniform over the cone of directions the sphere fills, as seen from p (outside it)
+    fn sample_li(&self, p: Point, rng: &mut Rng) -> Option<LightSample>{
+        let r = self.r;
+        let to_cen = self.cen - p;
+        let dist2 = to_cen.dot(to_cen);
+        if dist2 <= r * r {
+            return None;
+        }
+        let dist = dist2.sqrt();
+        let cos_max = (1.0 - r * r / dist2).sqrt();
+        if cos_max >= 1.0 {
+            return None;                                    //-- too small to see from here
+        }
+        let cos_t = 1.0 - rng.next_f64() * (1.0 - cos_max);
+        let sin_t = (1.0 - cos_t * cos_t).max(0.0).sqrt();
+        let phi = 2.0 * PI * rng.next_f64();
+
+        let w = (1.0 / dist) * to_cen;
+        let (t, b) = sampler::onb(w);
+        let dir = (sin_t * phi.cos()) * t + (sin_t * phi.sin()) * b + cos_t * w;
+
+        //- near side of the sphere along dir, the root is clamped for rays grazing the rim
+        let along = dist * cos_t - (r * r - dist2 * sin_t * sin_t).max(0.0).sqrt();
+        Some(LightSample{dir, dist: along, li: self.radiance, spec: self.is, pdf: 1.0 / (2.0 * PI * (1.0 - cos_max))})
+    }
+} impl Stringable for SphereLight {
+    fn stringy(&self) -> String{
+        format!("sphere light at {} with r {}", self.cen.stringy(), self.r)
+    }
+}
+
+//---- Geometry Light: scene shapes with emissive materials, sampled as one light
+//- each part is a shape (also in the scene's objects) + the radiance its material emits from both faces
+pub struct GeometryLight{
+    pub parts: Vec<(Arc<dyn Hittable>, Point)>,
+    cdf: Vec<f64>                   //-- running total of part areas, for picking parts in proportion to area
+
+} impl GeometryLight {
+
+    //-- None if none of the parts has any area to sample
+    pub fn new(parts: Vec<(Arc<dyn Hittable>, Point)>) -> Option<GeometryLight>{
+        let mut total = 0.0;
+        let cdf: Vec<f64> = parts.iter().map(|(shape, _)| { total += shape.area(); total }).collect();
+        if total > 0.0 { Some(GeometryLight{parts, cdf}) } else { None }
+    }
+
+    pub fn area(&self) -> f64{
+        *self.cdf.last().unwrap_or(&0.0)
+    }
+
+} impl Light for GeometryLight {
+
+    fn is_delta(&self) -> bool{
+        false
+    }
+
+    //-- uniform over the total area, pdf 1 / A converted to solid angle at p
+    fn sample_li(&self, p: Point, rng: &mut Rng) -> Option<LightSample>{
+        let area = self.area();
+        let pick = rng.next_f64() * area;
+        let i = self.cdf.partition_point(|&c| c <= pick).min(self.parts.len() - 1);
+        let (shape, emission) = &self.parts[i];
+
+        let (pos, n) = shape.sample_surface(rng)?;
+        let to_light = pos - p;
+        let dist2 = to_light.dot(to_light);
+        let dist = dist2.sqrt();
+        let dir = (1.0 / dist) * to_light;
+        let cos_l = dir.dot(n).abs();
+        if dist <= 0.0 || cos_l <= 0.0 {
+            return None;
+        }
+        Some(LightSample{dir, dist, li: *emission, spec: *emission, pdf: dist2 / (area * cos_l)})
+    }
+
+    //-- stop the shadow ray just short of the light, or it would be blocked by the shape it is aimed at
+    fn occluded(&self, p: Point, n: Vec3, sample: &LightSample, hit_scene: &[Box<dyn Hittable>]) -> bool{
+        in_shadow(p, n, sample.dir, (1.0 - SHADOW_EPS) * sample.dist, hit_scene)
+    }
+} impl Stringable for GeometryLight {
+    fn stringy(&self) -> String{
+        format!("emissive geometry ({} shapes, area {})", self.parts.len(), self.area())
+    }
+}
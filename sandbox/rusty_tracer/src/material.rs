@@ -1,14 +1,23 @@
 // Bill Derksen - 8/21
 //-- material struct and functions!!!
 
-use crate::vmaths::{Point, Vec3};
+use std::sync::Arc;
 
+use crate::texture::{Bump, Texture};
+use crate::vmaths::{Point};
+
+#[derive(Clone)]
 pub struct Material{
     pub desc: String,
     pub kd: f64,
     pub ks: f64,
     pub alpha: f64,
-    pub base_color: Point
+    pub base_color: Point,
+    pub reflectivity: f64,          //-- 0 = no mirror reflection, 1 = perfect mirror
+    pub ior: Option<f64>,           //-- Some(index of refraction) makes this a clear dielectric (glass ~1.5), Phong terms unused
+    pub texture: Option<Arc<dyn Texture>>,      //-- replaces base_color when given
+    pub bump: Option<Bump>,                     //-- perturbs the shading normal
+    pub emission: Point                         //-- radiance given off from both faces, black for most materials
         
 } impl Default for Material{
     
@@ -18,11 +27,29 @@ pub struct Material{
             kd: 0.3,
             ks: 0.5,
             alpha: 50.0,
-            base_color: Point::gen(0.2, 0.2, 0.2)
+            base_color: Point::gen(0.2, 0.2, 0.2),
+            reflectivity: 0.0,
+            ior: None,
+            texture: None,
+            bump: None,
+            emission: Point::default()
         }
     }
 } impl Material {
 
+    //-- surface color at a hit: the texture if there is one, else the base color
+    pub fn color_at(&self, uv: (f64, f64), p: Point) -> Point{
+        match &self.texture {
+            Some(tex) => tex.value(uv, p),
+            None => self.base_color
+        }
+    }
+
+    //-- does it give off light of its own
+    pub fn is_emissive(&self) -> bool{
+        self.emission.max_elem() > 0.0
+    }
+
     //-- Initializers
     pub fn shiny_red() -> Material{
         Material{
@@ -30,7 +57,12 @@ pub struct Material{
             kd: 0.3,
             ks: 0.5,
             alpha: 50.0,
-            base_color: Point::gen(0.6, 0.2, 0.2)
+            base_color: Point::gen(0.6, 0.2, 0.2),
+            reflectivity: 0.0,
+            ior: None,
+            texture: None,
+            bump: None,
+            emission: Point::default()
         }
     }
 
@@ -40,7 +72,12 @@ pub struct Material{
             kd: 0.3,
             ks: 0.5,
             alpha: 50.0,
-            base_color: Point::gen(0.2, 0.2, 0.6)
+            base_color: Point::gen(0.2, 0.2, 0.6),
+            reflectivity: 0.0,
+            ior: None,
+            texture: None,
+            bump: None,
+            emission: Point::default()
         }
     }
 
@@ -50,7 +87,42 @@ pub struct Material{
             kd: 0.3,
             ks: 0.5,
             alpha: 50.0,
-            base_color: Point::gen(0.2, 0.6, 0.2)
+            base_color: Point::gen(0.2, 0.6, 0.2),
+            reflectivity: 0.0,
+            ior: None,
+            texture: None,
+            bump: None,
+            emission: Point::default()
+        }
+    }
+
+    pub fn mirror() -> Material{
+        Material{
+            desc: String::from("default mirror"),
+            kd: 0.1,
+            ks: 0.8,
+            alpha: 200.0,
+            base_color: Point::gen(0.05, 0.05, 0.05),
+            reflectivity: 0.9,
+            ior: None,
+            texture: None,
+            bump: None,
+            emission: Point::default()
+        }
+    }
+
+    pub fn glass() -> Material{
+        Material{
+            desc: String::from("default glass"),
+            kd: 0.0,
+            ks: 0.0,
+            alpha: 50.0,
+            base_color: Point::gen(0.0, 0.0, 0.0),
+            reflectivity: 0.0,
+            ior: Some(1.5),
+            texture: None,
+            bump: None,
+            emission: Point::default()
         }
     }
 }
\ No newline at end of file
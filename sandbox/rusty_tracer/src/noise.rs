@@ -0,0 +1,99 @@
+// Bill Derksen - 10/26
+//-- gradient (Perlin) noise and the fractal sums built from it, for procedural textures
+
+use crate::sampler::{Rng};
+use crate::vmaths::{Point};
+
+//-- most octaves fbm / turbulence sum, past this they are finer than any pixel and the frequency heads for overflow
+pub const MAX_OCTAVES: u32 = 16;
+
+//---- Perlin: improved gradient noise over the integer lattice, shuffled from a seed
+//- the same seed always gives the same noise field
+pub struct Perlin{
+    perm: [u8; 512]                 //-- shuffled 0..256, repeated so lookups never need to wrap
+
+} impl Perlin {
+
+    pub fn new(seed: u64) -> Perlin{
+        let mut table: [u8; 256] = [0; 256];
+        for (i, p) in table.iter_mut().enumerate() {
+            *p = i as u8;
+        }
+
+        //- Fisher-Yates shuffle
+        let mut rng = Rng::new(seed, 0x9e3779b9);
+        for i in (1..256).rev() {
+            let j = (rng.next_u32() % (i as u32 + 1)) as usize;
+            table.swap(i, j);
+        }
+
+        let mut perm = [0; 512];
+        for (i, p) in perm.iter_mut().enumerate() {
+            *p = table[i & 255];
+        }
+        Perlin{perm}
+    }
+
+    //-- noise at p, roughly in [-1, 1] and 0 at every lattice point
+    pub fn noise(&self, p: Point) -> f64{
+        let cell = Point::gen(p.x.floor(), p.y.floor(), p.z.floor());
+        let f = p - cell;
+        let (x, y, z) = ((cell.x as i64 & 255) as usize, (cell.y as i64 & 255) as usize, (cell.z as i64 & 255) as usize);
+        let (u, v, w) = (fade(f.x), fade(f.y), fade(f.z));
+
+        //- hash the 8 cell corners
+        let perm = &self.perm;
+        let a = perm[x] as usize + y;
+        let (aa, ab) = (perm[a] as usize + z, perm[a + 1] as usize + z);
+        let b = perm[x + 1] as usize + y;
+        let (ba, bb) = (perm[b] as usize + z, perm[b + 1] as usize + z);
+
+        lerp(w,
+            lerp(v,
+                lerp(u, grad(perm[aa], f.x, f.y, f.z), grad(perm[ba], f.x - 1.0, f.y, f.z)),
+                lerp(u, grad(perm[ab], f.x, f.y - 1.0, f.z), grad(perm[bb], f.x - 1.0, f.y - 1.0, f.z))),
+            lerp(v,
+                lerp(u, grad(perm[aa + 1], f.x, f.y, f.z - 1.0), grad(perm[ba + 1], f.x - 1.0, f.y, f.z - 1.0)),
+                lerp(u, grad(perm[ab + 1], f.x, f.y - 1.0, f.z - 1.0), grad(perm[bb + 1], f.x - 1.0, f.y - 1.0, f.z - 1.0))))
+    }
+
+    //-- fractal Brownian motion: octaves of noise, each lacunarity x the frequency and gain x the amplitude of the last
+    //- normalized by the total amplitude, so still roughly in [-1, 1]
+    pub fn fbm(&self, p: Point, octaves: u32, lacunarity: f64, gain: f64) -> f64{
+        self.octave_sum(p, octaves, lacunarity, gain, |n| n)
+    }
+
+    //-- fbm of |noise|, the creases where the noise crosses 0 give it a billowy look. In [0, 1]
+    pub fn turbulence(&self, p: Point, octaves: u32, lacunarity: f64, gain: f64) -> f64{
+        self.octave_sum(p, octaves, lacunarity, gain, f64::abs)
+    }
+
+    fn octave_sum(&self, p: Point, octaves: u32, lacunarity: f64, gain: f64, shape: fn(f64) -> f64) -> f64{
+        let (mut sum, mut total) = (0.0, 0.0);
+        let (mut freq, mut amp) = (1.0, 1.0);
+        for _ in 0..octaves.clamp(1, MAX_OCTAVES) {
+            sum += amp * shape(self.noise(freq * p));
+            total += amp;
+            freq *= lacunarity;
+            amp *= gain;
+        }
+        if total > 0.0 { sum / total } else { 0.0 }
+    }
+}
+
+//-- 6t^5 - 15t^4 + 10t^3, eases the blend so the noise has no creases at cell edges
+fn fade(t: f64) -> f64{
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(t: f64, a: f64, b: f64) -> f64{
+    a + t * (b - a)
+}
+
+//-- dot of (x, y, z) with one of the 12 cube edge directions, picked by the hash
+fn grad(hash: u8, x: f64, y: f64, z: f64) -> f64{
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 { y } else if h == 12 || h == 14 { x } else { z };
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
@@ -0,0 +1,252 @@
+// Bill Derksen - 10/26
+//-- Wavefront OBJ/MTL import: turns a mesh file into triangles
+//
+//  Supported OBJ statements: v, vt, vn, f (any polygon, fan triangulated so it should be convex),
+//  mtllib, usemtl. Negative (relative) indices are allowed. Everything else (o, g, s, l, ...) is skipped.
+//
+//  MTL materials map onto Material as: Kd -> base_color (and its brightest channel -> kd), Ks (mean) -> ks,
+//  Ns -> alpha, Ke -> emission. Faces share one Arc'd copy of their material.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::geometry::{Triangle};
+use crate::material::{Material};
+use crate::vmaths::{Point, Vec3};
+
+//---- Obj Error: an unreadable file, or a bad line in an .obj/.mtl file
+#[derive(Debug)]
+pub enum ObjError{
+    Io{path: PathBuf, err: io::Error},
+    Parse{path: PathBuf, line: usize, msg: String}
+
+} impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result{
+        match self {
+            ObjError::Io{path, err} => write!(f, "could not read {}: {}", path.display(), err),
+            ObjError::Parse{path, line, msg} => write!(f, "{}:{}: {}", path.display(), line, msg)
+        }
+    }
+} impl Error for ObjError {}
+
+//-- read an .obj file (and any .mtl files it names, relative to it) into triangles
+pub fn load_obj(path: &Path) -> Result<Vec<Triangle>, ObjError>{
+
+    let src = read(path)?;
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+
+    let mut positions: Vec<Point> = Vec::new();
+    let mut uvs: Vec<(f64, f64)> = Vec::new();
+    let mut normals: Vec<Vec3> = Vec::new();
+    let mut materials: HashMap<String, Arc<Material>> = HashMap::new();
+    let mut current_mat = Arc::new(Material::default());
+    let mut tris: Vec<Triangle> = Vec::new();
+
+    for (i, raw) in src.lines().enumerate() {
+        let line_no = i + 1;
+        let err = |msg: String| ObjError::Parse{path: path.to_path_buf(), line: line_no, msg};
+
+        let mut words = strip_comment(raw).split_whitespace();
+        let keyword = match words.next() {
+            Some(k) => k,
+            None => continue
+        };
+        let args: Vec<&str> = words.collect();
+
+        match keyword {
+            "v" => {
+                let v = nums(&args, 3, 4).map_err(err)?;
+                positions.push(Point::gen(v[0], v[1], v[2]));
+            }
+            "vt" => {
+                let v = nums(&args, 1, 3).map_err(err)?;
+                uvs.push((v[0], *v.get(1).unwrap_or(&0.0)));
+            }
+            "vn" => {
+                let v = nums(&args, 3, 3).map_err(err)?;
+                let n = Vec3::gen(v[0], v[1], v[2]);
+                if n.mag() == 0.0 {
+                    return Err(err(String::from("vertex normal has zero length")));
+                }
+                normals.push(n.unit());
+            }
+            "f" => {
+                if args.len() < 3 {
+                    return Err(err(format!("face needs at least 3 vertices, found {}", args.len())));
+                }
+                let corners = args.iter()
+                    .map(|a| parse_corner(a, positions.len(), uvs.len(), normals.len()))
+                    .collect::<Result<Vec<Corner>, String>>()
+                    .map_err(err)?;
+
+                //- vt/vn only used if every corner of the face has them
+                let has_uv = corners.iter().all(|c| c.uv.is_some());
+                let has_norm = corners.iter().all(|c| c.norm.is_some());
+
+                //- fan triangulation around the first corner
+                for k in 1..corners.len() - 1 {
+                    let (a, b, c) = (corners[0], corners[k], corners[k + 1]);
+                    let mut tri = Triangle::gen(positions[a.pos], positions[b.pos], positions[c.pos]);
+                    if tri.normal().mag().is_nan() {
+                        continue;                                    //-- zero area, can never be hit
+                    }
+                    if has_uv {
+                        tri.uvs = Some([uvs[a.uv.unwrap()], uvs[b.uv.unwrap()], uvs[c.uv.unwrap()]]);
+                    }
+                    if has_norm {
+                        tri.normals = Some([normals[a.norm.unwrap()], normals[b.norm.unwrap()], normals[c.norm.unwrap()]]);
+                    }
+                    tri.material = Arc::clone(&current_mat);
+                    tris.push(tri);
+                }
+            }
+            "mtllib" => {
+                if args.is_empty() {
+                    return Err(err(String::from("mtllib expects a file name")));
+                }
+                for name in args {
+                    materials.extend(load_mtl(&dir.join(name))?.into_iter().map(|(k, m)| (k, Arc::new(m))));
+                }
+            }
+            "usemtl" => {
+                let name = match args.first() {
+                    Some(n) => *n,
+                    None => return Err(err(String::from("usemtl expects a material name")))
+                };
+                current_mat = match materials.get(name) {
+                    Some(m) => Arc::clone(m),
+                    None => return Err(err(format!("unknown material `{}` (missing mtllib?)", name)))
+                };
+            }
+            _ => {}
+        }
+    }
+
+    Ok(tris)
+}
+
+//-- read an .mtl file into materials by name
+pub fn load_mtl(path: &Path) -> Result<HashMap<String, Material>, ObjError>{
+
+    let src = read(path)?;
+    let mut materials: HashMap<String, Material> = HashMap::new();
+    let mut current: Option<String> = None;
+
+    for (i, raw) in src.lines().enumerate() {
+        let line_no = i + 1;
+        let err = |msg: String| ObjError::Parse{path: path.to_path_buf(), line: line_no, msg};
+
+        let mut words = strip_comment(raw).split_whitespace();
+        let keyword = match words.next() {
+            Some(k) => k,
+            None => continue
+        };
+        let args: Vec<&str> = words.collect();
+
+        if keyword == "newmtl" {
+            let name = match args.first() {
+                Some(n) => n.to_string(),
+                None => return Err(err(String::from("newmtl expects a material name")))
+            };
+            materials.insert(name.clone(), Material{desc: name.clone(), ..Material::default()});
+            current = Some(name);
+            continue;
+        }
+
+        //- Kd/Ks/Ns/Ke edit the current material, the rest (Ka, d, illum, map_*, ...) are skipped
+        if !matches!(keyword, "Kd" | "Ks" | "Ns" | "Ke") {
+            continue;
+        }
+        let mat = match &current {
+            Some(name) => materials.get_mut(name).unwrap(),
+            None => return Err(err(format!("`{}` before any newmtl", keyword)))
+        };
+        match keyword {
+            "Kd" => {
+                let v = nums(&args, 3, 3).map_err(err)?;
+                mat.base_color = Point::gen(v[0], v[1], v[2]);
+                mat.kd = v[0].max(v[1]).max(v[2]).clamp(0.0, 1.0);
+            }
+            "Ks" => {
+                let v = nums(&args, 3, 3).map_err(err)?;
+                mat.ks = (v[0] + v[1] + v[2]) / 3.0;
+            }
+            "Ns" => {
+                mat.alpha = nums(&args, 1, 1).map_err(err)?[0];
+            }
+            "Ke" => {
+                let v = nums(&args, 3, 3).map_err(err)?;
+                mat.emission = Point::gen(v[0].max(0.0), v[1].max(0.0), v[2].max(0.0));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(materials)
+}
+
+// <<<<<<<<<<<<<<<<<<<<  PARSING HELPERS >>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>
+
+//---- Corner: 0-based indices of one face vertex (v/vt/vn)
+#[derive(Copy, Clone)]
+struct Corner{
+    pos: usize,
+    uv: Option<usize>,
+    norm: Option<usize>
+}
+
+fn read(path: &Path) -> Result<String, ObjError>{
+    fs::read_to_string(path).map_err(|err| ObjError::Io{path: path.to_path_buf(), err})
+}
+
+fn strip_comment(line: &str) -> &str{
+    match line.find('#') {
+        Some(i) => &line[..i],
+        None => line
+    }
+}
+
+//-- between min and max numbers
+fn nums(args: &[&str], min: usize, max: usize) -> Result<Vec<f64>, String>{
+    if args.len() < min || args.len() > max {
+        let expected = if min == max { min.to_string() } else { min.to_string() + " to " + &max.to_string() };
+        return Err(format!("expected {} numbers, found {}", expected, args.len()));
+    }
+    args.iter().map(|a| match a.parse::<f64>() {
+        Ok(v) if v.is_finite() => Ok(v),
+        _ => Err(format!("expected a number, found `{}`", a))
+    }).collect()
+}
+
+//-- one face corner: v, v/vt, v//vn or v/vt/vn
+fn parse_corner(word: &str, n_pos: usize, n_uv: usize, n_norm: usize) -> Result<Corner, String>{
+    let parts: Vec<&str> = word.split('/').collect();
+    if parts.len() > 3 {
+        return Err(format!("bad face vertex `{}`", word));
+    }
+    let pos = index(parts[0], n_pos, "vertex", word)?;
+    let uv = match parts.get(1) {
+        Some(p) if !p.is_empty() => Some(index(p, n_uv, "texture coord", word)?),
+        _ => None
+    };
+    let norm = match parts.get(2) {
+        Some(p) if !p.is_empty() => Some(index(p, n_norm, "normal", word)?),
+        _ => None
+    };
+    Ok(Corner{pos, uv, norm})
+}
+
+//-- 1-based (or negative, counting back from the latest) OBJ index -> 0-based
+fn index(s: &str, len: usize, what: &str, word: &str) -> Result<usize, String>{
+    let i: i64 = s.parse().map_err(|_| format!("bad {} index `{}` in `{}`", what, s, word))?;
+    let resolved = if i > 0 { i - 1 } else { len as i64 + i };
+    if i == 0 || resolved < 0 || resolved >= len as i64 {
+        return Err(format!("{} index {} out of range in `{}` ({} defined so far)", what, i, word, len));
+    }
+    Ok(resolved as usize)
+}
@@ -0,0 +1,99 @@
+// Bill Derksen - 10/26
+//-- Monte Carlo path tracing: global illumination by following random light paths from the camera
+//
+//  Surfaces are Lambertian with albedo = material color (texture or base_color), plus a mirror part (reflectivity) and clear
+//  dielectrics (ior). Specular vs diffuse is picked at random in proportion to reflectivity/Fresnel,
+//  so those weights cancel out of the path throughput. Lights can't be hit by a bounced ray, so each
+//  is sampled directly at every diffuse hit (next event estimation): point-like lights exactly, area
+//  lights at one random point weighted by its solid angle pdf. Emissive geometry is both: its glow is
+//  added where a camera or mirror/glass ray hits it, and it is sampled as a light after diffuse bounces
+//  (where hitting it again would count it twice). Phong's kd/ks/alpha are not used.
+
+use std::f64::consts::PI;
+
+use crate::ray::{Ray};
+use crate::sampler::{self, Rng};
+use crate::scene::{Scene};
+use crate::shading::{closest_hit, DielectricSplit, SHADOW_EPS};
+use crate::vmaths::{Point, Vec3};
+
+//-- bounces always taken before Russian roulette can end a path
+const RR_MIN_BOUNCES: u32 = 3;
+
+//---- Trace one random path from the camera ray, returns its estimate of the linear rgb radiance
+//- max_depth caps the number of bounces, Russian roulette usually ends paths well before it
+pub fn trace_path(ray: &Ray, scene: &Scene, rng: &mut Rng, max_depth: u32) -> Point{
+
+    let mut radiance = Point::default();
+    let mut throughput = Point::gen(1.0, 1.0, 1.0);
+    let mut ray = *ray;
+    let mut saw_emitters = true;            //-- false after a diffuse bounce, whose direct light already counted them
+
+    for bounce in 0..=max_depth {
+        let hit_rec = match closest_hit(&ray, &scene.objects) {
+            Some(hit_rec) => hit_rec,
+            None => break                                                   //-- background: black
+        };
+        let mat = hit_rec.hit_mat;
+        let d: Vec3 = ray.dir.unit();
+
+        //-- glowing surface seen directly, or through mirrors/glass
+        if saw_emitters {
+            radiance = radiance + throughput.mul_elem(mat.emission);
+        }
+        saw_emitters = true;
+
+        if let Some(ior) = mat.ior {
+            //-- dielectric: reflect with the Fresnel probability, else refract
+            let split = DielectricSplit::at(d, hit_rec.norm, ior);
+            ray = match split.refracted {
+                Some(dir) if rng.next_f64() >= split.fresnel => Ray{origin: hit_rec.ip - SHADOW_EPS * split.n, dir},
+                _ => Ray{origin: hit_rec.ip + SHADOW_EPS * split.n, dir: split.reflected}
+            };
+        } else {
+            let n: Vec3 = hit_rec.facing_norm();
+
+            if rng.next_f64() < mat.reflectivity {
+                //-- mirror bounce
+                ray = Ray{origin: hit_rec.ip + SHADOW_EPS * n, dir: d - 2.0 * d.dot(n) * n};
+            } else {
+                //-- diffuse: gather the lights directly, then bounce in a cosine weighted direction
+                //- brdf (albedo / PI) * cos / pdf (cos / PI) leaves just the albedo
+                let albedo = hit_rec.color();
+                radiance = radiance + throughput.mul_elem(direct_light(hit_rec.ip, n, albedo, scene, rng));
+                ray = Ray{origin: hit_rec.ip + SHADOW_EPS * n, dir: sampler::cosine_hemisphere(n, rng)};
+                throughput = throughput.mul_elem(albedo);
+                saw_emitters = false;
+            }
+        }
+
+        //-- Russian roulette: end dim paths at random, boosting the survivors to stay unbiased
+        if bounce >= RR_MIN_BOUNCES {
+            let survive = throughput.max_elem().min(0.95);
+            if survive <= 0.0 || rng.next_f64() >= survive {
+                break;
+            }
+            throughput = throughput * (1.0 / survive);
+        }
+    }
+
+    radiance
+}
+
+//-- light reaching a diffuse point straight from each light (one sample each), scattered toward the viewer
+//- brdf (albedo / PI) * li * cos / pdf
+fn direct_light(ip: Point, n: Vec3, albedo: Point, scene: &Scene, rng: &mut Rng) -> Point{
+    let mut illu = Point::default();
+    for light in &scene.lights {
+        let sample = match light.sample_li(ip, rng) {
+            Some(sample) => sample,
+            None => continue
+        };
+        let cos = sample.dir.dot(n);
+        if cos <= 0.0 || light.occluded(ip, n, &sample, &scene.objects) {
+            continue;
+        }
+        illu = illu + (cos / (PI * sample.pdf)) * albedo.mul_elem(sample.li);
+    }
+    illu
+}
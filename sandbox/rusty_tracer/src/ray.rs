@@ -13,12 +13,11 @@ pub struct Ray{
 } impl Ray{
 
     pub fn at(&self, t: f64) -> Point{
-        let p = self.origin + ( self.dir * t);
-        return p;
+        self.origin + ( self.dir * t)
     }
 
 } impl Stringable for Ray{
     fn stringy(&self) -> String{
-        return String::from("P(t) = ".to_owned() + &self.origin.stringy() + " + t" + &self.dir.stringy());
+        "P(t) = ".to_owned() + &self.origin.stringy() + " + t" + &self.dir.stringy()
     }
 }
\ No newline at end of file
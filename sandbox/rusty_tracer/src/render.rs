@@ -0,0 +1,130 @@
+// Bill Derksen - 10/26
+//-- tile-based renderer: splits the image into tiles and shades them on worker threads
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+use crate::film::{Film};
+use crate::integrator::{Integrator};
+use crate::sampler::{self, Rng};
+use crate::scene::{Scene};
+use crate::vmaths::{Point};
+
+//-- default tile edge in pixels
+pub const TILE_SIZE: u32 = 32;
+
+//---- Render Settings: per-render knobs (image size, parallelism, sampling)
+#[derive(Copy, Clone)]
+pub struct RenderSettings{
+    pub width: u32,
+    pub height: u32,
+    pub threads: usize,
+    pub samples: u32,               //-- rays averaged per pixel
+    pub tile_size: u32              //-- edge of the square tiles handed to workers, doesn't change the image
+
+} impl Default for RenderSettings {
+    fn default() -> RenderSettings{
+        RenderSettings{width: 1920, height: 1080, threads: 1, samples: 1, tile_size: TILE_SIZE}
+    }
+}
+
+//---- Tile: pixel rect [x0, x1) x [y0, y1) of the image
+#[derive(Copy, Clone)]
+pub struct Tile{
+    pub x0: u32,
+    pub y0: u32,
+    pub x1: u32,
+    pub y1: u32
+
+} impl Tile {
+
+    //-- cover an img_w x img_h image with tiles, row by row (edge tiles may be smaller)
+    pub fn split(img_w: u32, img_h: u32, size: u32) -> Vec<Tile>{
+        let mut tiles = Vec::new();
+        for y0 in (0..img_h).step_by(size as usize) {
+            for x0 in (0..img_w).step_by(size as usize) {
+                tiles.push(Tile{x0, y0, x1: (x0 + size).min(img_w), y1: (y0 + size).min(img_h)});
+            }
+        }
+        tiles
+    }
+}
+
+//-- number of tiles render() will hand out, e.g. for sizing a progress bar
+pub fn tile_count(settings: &RenderSettings) -> u64{
+    let size = settings.tile_size.max(1);
+    let across = settings.width.div_ceil(size) as u64;
+    let down = settings.height.div_ceil(size) as u64;
+    across * down
+}
+
+//-- render the scene with the given integrator onto film (linear rgb, nothing clamped) using `settings.threads` workers
+//- each pixel only depends on its own coords, so the result is identical for any thread count
+//- on_tile is called once per finished tile, from the calling thread
+pub fn render(scene: &Scene, settings: &RenderSettings, integrator: &dyn Integrator, on_tile: &mut dyn FnMut()) -> Film{
+
+    let img_w = settings.width;
+    let img_h = settings.height;
+    let tiles = Tile::split(img_w, img_h, settings.tile_size.max(1));
+    let next_tile = AtomicUsize::new(0);
+    let mut film = Film::new(img_w, img_h);
+
+    thread::scope(|s| {
+        let (tx, rx) = mpsc::channel::<(Tile, Vec<Point>)>();
+
+        //-- workers: grab the next unrendered tile until none are left
+        for _ in 0..settings.threads.max(1) {
+            let tx = tx.clone();
+            let tiles = &tiles;
+            let next_tile = &next_tile;
+            s.spawn(move || {
+                loop {
+                    let i = next_tile.fetch_add(1, Ordering::Relaxed);
+                    if i >= tiles.len() {
+                        break;
+                    }
+                    let tile = tiles[i];
+                    let mut pixels = Vec::with_capacity(((tile.x1 - tile.x0) * (tile.y1 - tile.y0)) as usize);
+                    for y in tile.y0..tile.y1 {
+                        for x in tile.x0..tile.x1 {
+                            pixels.push(render_pixel(scene, settings, integrator, x, y));
+                        }
+                    }
+                    if tx.send((tile, pixels)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(tx);
+
+        //-- copy finished tiles onto the film as they come in
+        for (tile, pixels) in rx {
+            let mut colors = pixels.into_iter();
+            for y in tile.y0..tile.y1 {
+                for x in tile.x0..tile.x1 {
+                    film.set(x, y, colors.next().unwrap());
+                }
+            }
+            on_tile();
+        }
+    });
+
+    film
+}
+
+//-- shade pixel (x, y): the mean of `settings.samples` integrator estimates, in linear rgb
+//- one sample goes through the pixel center, more are spread over the pixel by stratified jitter
+pub fn render_pixel(scene: &Scene, settings: &RenderSettings, integrator: &dyn Integrator, x: u32, y: u32) -> Point{
+    let samples = settings.samples.max(1);
+    let mut rng = Rng::for_pixel(x, y);
+
+    let mut sum = Point::default();
+    for i in 0..samples {
+        let (dx, dy) = if samples == 1 { (0.5, 0.5) } else { sampler::stratified(i, samples, &mut rng) };
+        let ray = scene.camera.get_ray((x as f64 + dx) / settings.width as f64, (y as f64 + dy) / settings.height as f64, &mut rng);
+        sum = sum + integrator.radiance(&ray, scene, &mut rng);
+    }
+    (1.0 / samples as f64) * sum
+}
@@ -0,0 +1,87 @@
+// Bill Derksen - 10/26
+//-- random numbers + direction sampling for the stochastic parts of rendering
+
+use std::f64::consts::PI;
+
+use crate::vmaths::{Vec3};
+
+//---- Rng: PCG32 (XSH RR) generator, small and fast enough to make one per pixel
+//- seeded from pixel coords so a render comes out the same for any thread count
+pub struct Rng{
+    state: u64,
+    inc: u64
+
+} impl Rng {
+
+    const MULT: u64 = 6364136223846793005;
+
+    pub fn new(seed: u64, stream: u64) -> Rng{
+        let mut rng = Rng{state: 0, inc: (stream << 1) | 1};
+        rng.next_u32();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.next_u32();
+        rng
+    }
+
+    //-- generator for one pixel
+    pub fn for_pixel(x: u32, y: u32) -> Rng{
+        Rng::new(((y as u64) << 32) | (x as u64), 0)
+    }
+
+    pub fn next_u32(&mut self) -> u32{
+        let old = self.state;
+        self.state = old.wrapping_mul(Rng::MULT).wrapping_add(self.inc);
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        let rot = (old >> 59) as u32;
+        xorshifted.rotate_right(rot)
+    }
+
+    //-- uniform in [0, 1)
+    pub fn next_f64(&mut self) -> f64{
+        (self.next_u32() as f64) / 4294967296.0
+    }
+}
+
+//-- offset in [0, 1)^2 for sample i of n in a pixel, jittered within a sqrt(n) x sqrt(n) grid of strata
+//- when n is not a square, the samples past the largest square grid are jittered over the whole pixel
+pub fn stratified(i: u32, n: u32, rng: &mut Rng) -> (f64, f64){
+    let side = (n as f64).sqrt() as u32;
+    let (jx, jy) = (rng.next_f64(), rng.next_f64());
+    if i >= side * side {
+        return (jx, jy);
+    }
+    let cell = 1.0 / side as f64;
+    (((i % side) as f64 + jx) * cell, ((i / side) as f64 + jy) * cell)
+}
+
+//-- uniform point on the unit disk, by Shirley's concentric mapping of the unit square (keeps strata compact)
+pub fn concentric_disk(rng: &mut Rng) -> (f64, f64){
+    let a = 2.0 * rng.next_f64() - 1.0;
+    let b = 2.0 * rng.next_f64() - 1.0;
+    if a == 0.0 && b == 0.0 {
+        return (0.0, 0.0);
+    }
+    let (r, phi) = if a.abs() > b.abs() {
+        (a, 0.25 * PI * (b / a))
+    } else {
+        (b, 0.5 * PI - 0.25 * PI * (a / b))
+    };
+    (r * phi.cos(), r * phi.sin())
+}
+
+//-- two unit vectors that make an orthonormal basis with unit vector n
+pub fn onb(n: Vec3) -> (Vec3, Vec3){
+    let a = if n.x.abs() > 0.9 { Vec3::gen(0.0, 1.0, 0.0) } else { Vec3::gen(1.0, 0.0, 0.0) };
+    let t = n.cross(a).unit();
+    (t, n.cross(t))
+}
+
+//-- cosine weighted direction on the hemisphere around unit vector n, pdf = cos(theta) / PI
+pub fn cosine_hemisphere(n: Vec3, rng: &mut Rng) -> Vec3{
+    let r1 = rng.next_f64();
+    let r2 = rng.next_f64();
+    let phi = 2.0 * PI * r1;
+    let r = r2.sqrt();
+    let (t, b) = onb(n);
+    (r * phi.cos()) * t + (r * phi.sin()) * b + (1.0 - r2).sqrt() * n
+}
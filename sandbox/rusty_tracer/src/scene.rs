@@ -0,0 +1,663 @@
+// Bill Derksen - 10/26
+//-- scene description files: parse a text scene into a camera, lights, and hittable objects
+//
+//  Format: one directive per line, '#' starts a comment. A directive is a keyword followed by
+//  key/value attributes in any order:
+//
+//      camera   eye 0 0 -30  target 0 0 0  up 0 1 0  vfov 17.64  aspect 1.7778
+//      material chalk  kd 0.6  ks 0.1  alpha 10  color 0.8 0.8 0.8  refl 0
+//      material water  ior 1.33
+//      texture  tiles  checker  even 1 1 1  odd 0.1 0.1 0.1  size 2
+//      texture  wood   image  file textures/wood.png  wrap repeat
+//      texture  clouds fbm  scale 2  octaves 6  lacunarity 2  gain 0.5  seed 7  low 0.2 0.3 0.8  high 1 1 1
+//      material floor  kd 0.8  tex tiles  bump clouds 0.2
+//      material lamp   color 1 1 1  emit 15 15 15
+//      light    point  pos 0 3.9 -1  id 16 16 16  is 16 16 16
+//      light    directional  dir 1 -2 1  id 1 1 1  is 1 1 1
+//      light    spot   pos 0 3.9 -1  dir 0 -1 0  angle 30  inner 20  id 16 16 16
+//      light    rect   y 3.9  x -1 1  z -1 1  face down  radiance 15 15 15
+//      light    sphere cen 0 3 0  r 0.5  radiance 20 20 20
+//      sphere   cen -2 1 0  r 1.6  mat shiny_blue
+//      xyrect   z 4  x -8 8  y -4 4  mat chalk
+//      xzrect   y -4  x -8 8  z -4 4
+//      yzrect   x 8  y -4 4  z -4 4
+//      triangle v0 -1 0 0  v1 1 0 0  v2 0 1 0  mat chalk
+//      mesh     file models/bunny.obj  scale 10  offset 0 -4 0  mat chalk
+//      aabox    min -4 -2 -4  max 2 2 2  mat chalk
+//      bbox     cen 0 0 -5  size 1 1 1  mat shiny_red
+//
+//  Camera attributes are all optional (defaults shown above, vfov is vertical and in degrees).
+//  `aperture R  focus D` gives it a thin lens of radius R focused D ahead (default: the target distance).
+//  `projection orthographic  height H` views a H tall slice head-on (default: the view height at the target),
+//  `projection equirect` captures the full 360 x 180 degree panorama around the eye (use a 2:1 image).
+//  Materials must be declared before they are used. "default", "shiny_red", "shiny_blue",
+//  "shiny_green", "mirror" and "glass" are always available. Giving `ior` makes a material a
+//  clear dielectric. Textures (`solid color ...`, `checker even ... odd ... size S`, `image file F wrap
+//  repeat|mirror|clamp`) must be declared before a material uses them with `tex`, and replace its color.
+//  Image files are PNG, relative to the scene file. Noise textures (`perlin`, `fbm`, `turbulence`,
+//  `marble`, `wood`) blend low -> high in world space and are the same for the same seed; any texture
+//  can also `bump` a material's normals by its height times a strength. Mesh files are OBJ (see obj.rs),
+//  relative to the scene file; `mat` on a mesh overrides its MTL materials.
+//  Point and spot lights fall off with the inverse square of distance, id / is are their diffuse /
+//  specular intensities. A directional light shines along `dir` everywhere, a spot light lights a cone
+//  `angle` degrees around `dir`, fading out past `inner`. Area lights (`rect`, a horizontal xz rect lit
+//  on one `face`, down by default, and `sphere`) cast soft shadows, their `is` defaults to the radiance.
+//  No light is drawn, so keep geometry off area light surfaces. Instead, any object whose material has
+//  `emit` glows (from both faces) and is sampled as a light automatically.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::aabb::{Aabb};
+use crate::camera::{Camera, CameraModel, Equirectangular, Orthographic};
+use crate::geometry::{Sphere, Triangle, XYRect, XZRect, YZRect, AABox, BBox};
+use crate::hittable::{Hittable};
+use crate::light::{DirectionalLight, GeometryLight, Light, PointLight, RectLight, SphereLight, SpotLight};
+use crate::material::{Material};
+use crate::noise::{MAX_OCTAVES};
+use crate::obj;
+use crate::texture::{Bump, Checker, ImageTexture, NoiseKind, NoiseTexture, Solid, Texture, WrapMode};
+use crate::vmaths::{Point};
+
+//---- Scene: everything needed to render a frame
+pub struct Scene{
+    pub camera: Box<dyn CameraModel>,
+    pub lights: Vec<Box<dyn Light>>,
+    pub objects: Vec<Box<dyn Hittable>>
+
+} impl Scene {
+
+    //-- read and parse a scene file from disk, mesh paths are relative to its folder
+    pub fn load(path: &Path) -> Result<Scene, SceneError>{
+        let src = fs::read_to_string(path).map_err(SceneError::Io)?;
+        Scene::parse_in(&src, path.parent().unwrap_or_else(|| Path::new("")))
+    }
+
+    //-- parse scene text, line by line, mesh paths are relative to the working dir
+    pub fn parse(src: &str) -> Result<Scene, SceneError>{
+        Scene::parse_in(src, Path::new(""))
+    }
+
+    //-- parse scene text with mesh paths relative to base_dir
+    pub fn parse_in(src: &str, base_dir: &Path) -> Result<Scene, SceneError>{
+        let mut builder = SceneBuilder::new(base_dir);
+        for (i, line) in src.lines().enumerate() {
+            let toks = tokenize(i + 1, line);
+            if !toks.is_empty() {
+                builder.directive(&toks)?;
+            }
+        }
+        Ok(builder.finish())
+    }
+
+    //-- box around every object in the scene
+    pub fn bounds(&self) -> Aabb{
+        self.objects.iter().fold(Aabb::empty(), |b, obj| b.union(&obj.bounding_box()))
+    }
+}
+
+//---- Scene Error: io failures, or a parse failure at a 1-based line + column
+#[derive(Debug)]
+pub enum SceneError{
+    Io(io::Error),
+    Parse{line: usize, col: usize, msg: String}
+
+} impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result{
+        match self {
+            SceneError::Io(e) => write!(f, "could not read scene: {}", e),
+            SceneError::Parse{line, col, msg} => write!(f, "line {}, col {}: {}", line, col, msg)
+        }
+    }
+} impl Error for SceneError {}
+
+// <<<<<<<<<<<<<<<<<<<<  PARSING HELPERS >>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>
+
+//---- Token: a whitespace separated word + where it was found
+#[derive(Copy, Clone)]
+struct Token<'a>{
+    text: &'a str,
+    line: usize,
+    col: usize
+
+} impl<'a> Token<'a> {
+
+    fn err(&self, msg: String) -> SceneError{
+        SceneError::Parse{line: self.line, col: self.col, msg}
+    }
+
+    fn num(&self) -> Result<f64, SceneError>{
+        match self.text.parse::<f64>() {
+            Ok(v) if v.is_finite() => Ok(v),
+            _ => Err(self.err(format!("expected a number, found `{}`", self.text)))
+        }
+    }
+}
+
+//-- split a line into tokens, dropping anything after a '#'
+fn tokenize(line_no: usize, line: &str) -> Vec<Token<'_>>{
+    let line = match line.find('#') {
+        Some(i) => &line[..i],
+        None => line
+    };
+
+    let mut toks = Vec::new();
+    let mut start: Option<(usize, usize)> = None;          //-- (byte offset, column) of current word
+    for (col, (i, c)) in line.char_indices().enumerate() {
+        if c.is_whitespace() {
+            if let Some((s, s_col)) = start.take() {
+                toks.push(Token{text: &line[s..i], line: line_no, col: s_col});
+            }
+        } else if start.is_none() {
+            start = Some((i, col + 1));
+        }
+    }
+    if let Some((s, s_col)) = start {
+        toks.push(Token{text: &line[s..], line: line_no, col: s_col});
+    }
+    toks
+}
+
+//---- Attrs: key/value attributes of a directive, checked against a schema of (key, value count)
+struct Attrs<'a>{
+    head: Token<'a>,
+    vals: HashMap<&'a str, Vec<Token<'a>>>
+
+} impl<'a> Attrs<'a> {
+
+    fn parse(head: Token<'a>, toks: &[Token<'a>], schema: &[(&str, usize)]) -> Result<Attrs<'a>, SceneError>{
+        let mut vals = HashMap::new();
+        let mut i = 0;
+        while i < toks.len() {
+            let key = toks[i];
+            let arity = match schema.iter().find(|(k, _)| *k == key.text) {
+                Some((_, n)) => *n,
+                None => return Err(key.err(format!("unknown attribute `{}` for `{}`", key.text, head.text)))
+            };
+            if vals.contains_key(key.text) {
+                return Err(key.err(format!("duplicate attribute `{}`", key.text)));
+            }
+
+            //- too few values left, or the next key turns up where a value should be (`cen 0 0  r 1`)
+            let short = toks.len() - i - 1 < arity || toks[i + 1..i + 1 + arity].iter()
+                .any(|t| t.text.parse::<f64>().is_err() && schema.iter().any(|(k, _)| *k == t.text));
+            if short {
+                return Err(key.err(format!("`{}` expects {} value(s)", key.text, arity)));
+            }
+            vals.insert(key.text, toks[i + 1..i + 1 + arity].to_vec());
+            i += 1 + arity;
+        }
+        Ok(Attrs{head, vals})
+    }
+
+    fn missing(&self, key: &str) -> SceneError{
+        self.head.err(format!("`{}` is missing required attribute `{}`", self.head.text, key))
+    }
+
+    fn nums(&self, key: &str) -> Result<Option<Vec<f64>>, SceneError>{
+        match self.vals.get(key) {
+            Some(toks) => Ok(Some(toks.iter().map(|t| t.num()).collect::<Result<Vec<f64>, SceneError>>()?)),
+            None => Ok(None)
+        }
+    }
+
+    fn num(&self, key: &str) -> Result<Option<f64>, SceneError>{
+        Ok(self.nums(key)?.map(|v| v[0]))
+    }
+
+    //-- number in [0, 1], e.g. a reflectivity
+    fn unit_num(&self, key: &str) -> Result<Option<f64>, SceneError>{
+        match self.num(key)? {
+            Some(v) if !(0.0..=1.0).contains(&v) => Err(self.vals[key][0].err(format!("`{}` must be between 0 and 1", key))),
+            v => Ok(v)
+        }
+    }
+
+    //-- whole number >= 0, e.g. a seed
+    fn count(&self, key: &str) -> Result<Option<u64>, SceneError>{
+        match self.num(key)? {
+            Some(v) if v < 0.0 || v.fract() != 0.0 || v > u32::MAX as f64 => {
+                Err(self.vals[key][0].err(format!("`{}` must be a whole number >= 0", key)))
+            }
+            v => Ok(v.map(|v| v as u64))
+        }
+    }
+
+    fn point(&self, key: &str) -> Result<Option<Point>, SceneError>{
+        Ok(self.nums(key)?.map(|v| Point::gen(v[0], v[1], v[2])))
+    }
+
+    fn req_num(&self, key: &str) -> Result<f64, SceneError>{
+        self.num(key)?.ok_or_else(|| self.missing(key))
+    }
+
+    fn req_point(&self, key: &str) -> Result<Point, SceneError>{
+        self.point(key)?.ok_or_else(|| self.missing(key))
+    }
+
+    //-- positive scalar, e.g. a radius
+    fn req_positive(&self, key: &str) -> Result<f64, SceneError>{
+        let v = self.req_num(key)?;
+        if v <= 0.0 {
+            return Err(self.vals[key][0].err(format!("`{}` must be positive", key)));
+        }
+        Ok(v)
+    }
+
+    //-- increasing (lo, hi) pair, e.g. a rect extent
+    fn req_range(&self, key: &str) -> Result<(f64, f64), SceneError>{
+        let v = self.nums(key)?.ok_or_else(|| self.missing(key))?;
+        if v[0] >= v[1] {
+            return Err(self.vals[key][0].err(format!("`{}` range must be increasing, got {} {}", key, v[0], v[1])));
+        }
+        Ok((v[0], v[1]))
+    }
+
+    fn name(&self, key: &str) -> Option<Token<'a>>{
+        self.vals.get(key).map(|v| v[0])
+    }
+}
+
+//-- camera from its directive's attributes, perspective unless `projection` says otherwise
+fn camera(head: Token, a: &Attrs) -> Result<Box<dyn CameraModel>, SceneError>{
+
+    let projection = a.name("projection");
+    let kind = projection.map(|t| t.text).unwrap_or("perspective");
+    let only_for = |key: &str, kind_needed: &str| -> Result<(), SceneError>{
+        match a.vals.get(key) {
+            Some(toks) if kind != kind_needed => Err(toks[0].err(format!("`{}` only applies to {} cameras", key, kind_needed))),
+            _ => Ok(())
+        }
+    };
+    only_for("aperture", "perspective")?;
+    only_for("focus", "perspective")?;
+    only_for("height", "orthographic")?;
+
+    let def = Camera::new();
+    let vfov = match a.num("vfov")? {
+        Some(v) if v <= 0.0 || v >= 180.0 => return Err(a.vals["vfov"][0].err(String::from("`vfov` must be between 0 and 180 degrees"))),
+        Some(v) => v,
+        None => def.vfov
+    };
+    let aspect = match a.num("aspect")? {
+        Some(v) if v <= 0.0 => return Err(a.vals["aspect"][0].err(String::from("`aspect` must be positive"))),
+        Some(v) => v,
+        None => def.aspect
+    };
+    let cam = Camera::look_at(
+        a.point("eye")?.unwrap_or(def.eye),
+        a.point("target")?.unwrap_or(def.target),
+        a.point("up")?.unwrap_or(def.up),
+        vfov, aspect);
+    if cam.is_degenerate() {
+        return Err(head.err(String::from("camera needs eye != target and an up vector that is not along the view direction")));
+    }
+
+    match kind {
+        "perspective" => {
+            let aperture = match a.num("aperture")? {
+                Some(v) if v < 0.0 => return Err(a.vals["aperture"][0].err(String::from("`aperture` can't be negative"))),
+                Some(v) => v,
+                None => 0.0
+            };
+            let focus = match a.num("focus")? {
+                Some(v) if v <= 0.0 => return Err(a.vals["focus"][0].err(String::from("`focus` must be positive"))),
+                Some(v) => v,
+                None => cam.focus_dist
+            };
+            Ok(Box::new(cam.with_lens(aperture, focus)))
+        }
+        "orthographic" => {
+            let height = match a.num("height")? {
+                Some(v) if v <= 0.0 => return Err(a.vals["height"][0].err(String::from("`height` must be positive"))),
+                Some(v) => v,
+                None => cam.view_height()
+            };
+            Ok(Box::new(Orthographic{view: cam, height}))
+        }
+        "equirect" => Ok(Box::new(Equirectangular{view: cam})),
+        _ => Err(projection.unwrap().err(format!("unknown projection `{}`, expected perspective, orthographic or equirect", kind)))
+    }
+}
+
+//---- Scene Builder: accumulates directives into a scene
+struct SceneBuilder{
+    base_dir: PathBuf,
+    camera: Option<Box<dyn CameraModel>>,
+    materials: HashMap<String, Material>,
+    textures: HashMap<String, Arc<dyn Texture>>,
+    lights: Vec<Box<dyn Light>>,
+    objects: Vec<Box<dyn Hittable>>
+
+} impl SceneBuilder {
+
+    fn new(base_dir: &Path) -> SceneBuilder{
+        let mut materials = HashMap::new();
+        materials.insert(String::from("default"), Material::default());
+        materials.insert(String::from("shiny_red"), Material::shiny_red());
+        materials.insert(String::from("shiny_blue"), Material::shiny_blue());
+        materials.insert(String::from("shiny_green"), Material::shiny_green());
+        materials.insert(String::from("mirror"), Material::mirror());
+        materials.insert(String::from("glass"), Material::glass());
+        SceneBuilder{base_dir: base_dir.to_path_buf(), camera: None, materials, textures: HashMap::new(), lights: Vec::new(), objects: Vec::new()}
+    }
+
+    fn finish(self) -> Scene{
+        Scene{camera: self.camera.unwrap_or_else(|| Box::new(Camera::new())), lights: self.lights, objects: self.objects}
+    }
+
+    //-- look up the material named by `mat`, or the default material if not given
+    fn material(&self, attrs: &Attrs) -> Result<Material, SceneError>{
+        match attrs.name("mat") {
+            Some(tok) => match self.materials.get(tok.text) {
+                Some(m) => Ok(m.clone()),
+                None => Err(tok.err(format!("unknown material `{}`", tok.text)))
+            },
+            None => Ok(Material::default())
+        }
+    }
+
+    //-- add an object to the scene, and as a light too if it glows (emission is its material's)
+    fn add_object(&mut self, obj: Box<dyn Hittable>, emission: Point){
+        self.add_objects(vec![(obj, emission)]);
+    }
+
+    //-- add objects from one directive, the glowing ones become a single light together
+    fn add_objects(&mut self, objs: Vec<(Box<dyn Hittable>, Point)>){
+        let mut parts = Vec::new();
+        for (obj, emission) in objs {
+            if emission.max_elem() > 0.0 {
+                let shared: Arc<dyn Hittable> = Arc::from(obj);
+                self.objects.push(Box::new(Arc::clone(&shared)));
+                parts.push((shared, emission));
+            } else {
+                self.objects.push(obj);
+            }
+        }
+        if let Some(light) = GeometryLight::new(parts) {
+            self.lights.push(Box::new(light));
+        }
+    }
+
+    //-- toks is never empty
+    fn directive(&mut self, toks: &[Token]) -> Result<(), SceneError>{
+        let head = toks[0];
+        match head.text {
+            "camera" => {
+                if self.camera.is_some() {
+                    return Err(head.err(String::from("camera is already defined")));
+                }
+                let a = Attrs::parse(head, &toks[1..], &[("projection", 1), ("eye", 3), ("target", 3), ("up", 3), ("vfov", 1),
+                    ("aspect", 1), ("aperture", 1), ("focus", 1), ("height", 1)])?;
+                self.camera = Some(camera(head, &a)?);
+            }
+            "material" => {
+                let name = match toks.get(1) {
+                    Some(t) => *t,
+                    None => return Err(head.err(String::from("`material` expects a name")))
+                };
+                if self.materials.contains_key(name.text) {
+                    return Err(name.err(format!("material `{}` is already defined", name.text)));
+                }
+                let a = Attrs::parse(head, &toks[2..], &[("kd", 1), ("ks", 1), ("alpha", 1), ("color", 3), ("refl", 1), ("ior", 1),
+                    ("tex", 1), ("bump", 2), ("emit", 3)])?;
+                let mut mat = Material{desc: String::from(name.text), ..Material::default()};
+                if let Some(kd) = a.num("kd")? { mat.kd = kd; }
+                if let Some(ks) = a.num("ks")? { mat.ks = ks; }
+                if let Some(alpha) = a.num("alpha")? { mat.alpha = alpha; }
+                if let Some(c) = a.point("color")? { mat.base_color = c; }
+                if let Some(r) = a.unit_num("refl")? { mat.reflectivity = r; }
+                if let Some(e) = a.point("emit")? {
+                    if e.x < 0.0 || e.y < 0.0 || e.z < 0.0 {
+                        return Err(a.vals["emit"][0].err(String::from("`emit` must not be negative")));
+                    }
+                    mat.emission = e;
+                }
+                if a.num("ior")?.is_some() { mat.ior = Some(a.req_positive("ior")?); }
+                if let Some(tok) = a.name("tex") {
+                    match self.textures.get(tok.text) {
+                        Some(tex) => mat.texture = Some(Arc::clone(tex)),
+                        None => return Err(tok.err(format!("unknown texture `{}`", tok.text)))
+                    }
+                }
+                if let Some(vals) = a.vals.get("bump") {
+                    match self.textures.get(vals[0].text) {
+                        Some(tex) => mat.bump = Some(Bump{tex: Arc::clone(tex), strength: vals[1].num()?}),
+                        None => return Err(vals[0].err(format!("unknown texture `{}`", vals[0].text)))
+                    }
+                }
+                self.materials.insert(String::from(name.text), mat);
+            }
+            "texture" => {
+                let (name, kind) = match (toks.get(1), toks.get(2)) {
+                    (Some(n), Some(k)) => (*n, *k),
+                    _ => return Err(head.err(String::from("`texture` expects a name and a kind, e.g. `texture NAME checker ...`")))
+                };
+                if self.textures.contains_key(name.text) {
+                    return Err(name.err(format!("texture `{}` is already defined", name.text)));
+                }
+                let tex: Arc<dyn Texture> = match kind.text {
+                    "solid" => {
+                        let a = Attrs::parse(head, &toks[3..], &[("color", 3)])?;
+                        Arc::new(Solid{color: a.req_point("color")?})
+                    }
+                    "checker" => {
+                        let a = Attrs::parse(head, &toks[3..], &[("even", 3), ("odd", 3), ("size", 1)])?;
+                        let size = match a.num("size")? {
+                            Some(_) => a.req_positive("size")?,
+                            None => 1.0
+                        };
+                        Arc::new(Checker{even: a.req_point("even")?, odd: a.req_point("odd")?, size})
+                    }
+                    "image" => {
+                        let a = Attrs::parse(head, &toks[3..], &[("file", 1), ("wrap", 1)])?;
+                        let file = a.name("file").ok_or_else(|| a.missing("file"))?;
+                        let wrap = match a.name("wrap") {
+                            None => WrapMode::Repeat,
+                            Some(tok) => match tok.text {
+                                "repeat" => WrapMode::Repeat,
+                                "mirror" => WrapMode::Mirror,
+                                "clamp" => WrapMode::Clamp,
+                                _ => return Err(tok.err(format!("unknown wrap mode `{}`, expected repeat, mirror or clamp", tok.text)))
+                            }
+                        };
+                        Arc::new(ImageTexture::load(&self.base_dir.join(file.text), wrap).map_err(|e| file.err(e.to_string()))?)
+                    }
+                    "perlin" | "fbm" | "turbulence" | "marble" | "wood" => {
+                        let a = Attrs::parse(head, &toks[3..], &[("scale", 1), ("octaves", 1), ("lacunarity", 1), ("gain", 1),
+                            ("seed", 1), ("low", 3), ("high", 3)])?;
+                        let noise_kind = match kind.text {
+                            "perlin" => NoiseKind::Perlin,
+                            "fbm" => NoiseKind::Fbm,
+                            "turbulence" => NoiseKind::Turbulence,
+                            "marble" => NoiseKind::Marble,
+                            _ => NoiseKind::Wood
+                        };
+                        let seed = a.count("seed")?.unwrap_or(0);
+                        let mut tex = NoiseTexture::new(noise_kind, seed);
+                        if a.num("scale")?.is_some() { tex.scale = a.req_positive("scale")?; }
+                        if let Some(n) = a.count("octaves")? {
+                            if n == 0 || n > MAX_OCTAVES as u64 {
+                                return Err(a.vals["octaves"][0].err(format!("`octaves` must be a whole number from 1 to {}", MAX_OCTAVES)));
+                            }
+                            tex.octaves = n as u32;
+                        }
+                        if a.num("lacunarity")?.is_some() { tex.lacunarity = a.req_positive("lacunarity")?; }
+                        if a.num("gain")?.is_some() { tex.gain = a.req_positive("gain")?; }
+                        if let Some(c) = a.point("low")? { tex.low = c; }
+                        if let Some(c) = a.point("high")? { tex.high = c; }
+                        Arc::new(tex)
+                    }
+                    _ => return Err(kind.err(format!("unknown texture kind `{}`, expected solid, checker, image, perlin, fbm, \
+                        turbulence, marble or wood", kind.text)))
+                };
+                self.textures.insert(String::from(name.text), tex);
+            }
+            "light" => {
+                let kind = match toks.get(1) {
+                    Some(t) => *t,
+                    None => return Err(head.err(String::from("`light` expects a kind, e.g. `light point ...`")))
+                };
+                let white = Point::gen(1.0, 1.0, 1.0);
+                let light: Box<dyn Light> = match kind.text {
+                    "point" => {
+                        let a = Attrs::parse(head, &toks[2..], &[("pos", 3), ("id", 3), ("is", 3)])?;
+                        let mut light = PointLight::new();
+                        light.pos = a.req_point("pos")?;
+                        if let Some(id) = a.point("id")? { light.id = id; }
+                        if let Some(is) = a.point("is")? { light.is = is; }
+                        Box::new(light)
+                    }
+                    "directional" => {
+                        let a = Attrs::parse(head, &toks[2..], &[("dir", 3), ("id", 3), ("is", 3)])?;
+                        let dir = a.req_point("dir")?;
+                        if dir.mag() <= 0.0 {
+                            return Err(a.vals["dir"][0].err(String::from("`dir` must not be zero")));
+                        }
+                        Box::new(DirectionalLight{dir, id: a.point("id")?.unwrap_or(white), is: a.point("is")?.unwrap_or(white)})
+                    }
+                    "spot" => {
+                        let a = Attrs::parse(head, &toks[2..], &[("pos", 3), ("dir", 3), ("angle", 1), ("inner", 1), ("id", 3), ("is", 3)])?;
+                        let dir = a.req_point("dir")?;
+                        if dir.mag() <= 0.0 {
+                            return Err(a.vals["dir"][0].err(String::from("`dir` must not be zero")));
+                        }
+                        let angle = a.num("angle")?.unwrap_or(30.0);
+                        if !(angle > 0.0 && angle < 180.0) {
+                            return Err(a.vals["angle"][0].err(String::from("`angle` must be between 0 and 180 degrees")));
+                        }
+                        let inner = a.num("inner")?.unwrap_or(0.75 * angle);
+                        if !(0.0..=angle).contains(&inner) {
+                            return Err(a.vals["inner"][0].err(String::from("`inner` must be between 0 and `angle`")));
+                        }
+                        Box::new(SpotLight{pos: a.req_point("pos")?, dir, angle, inner, id: a.point("id")?.unwrap_or(white),
+                            is: a.point("is")?.unwrap_or(white)})
+                    }
+                    "rect" => {
+                        let a = Attrs::parse(head, &toks[2..], &[("y", 1), ("x", 2), ("z", 2), ("face", 1), ("radiance", 3), ("is", 3)])?;
+                        let (x0, x1) = a.req_range("x")?;
+                        let (z0, z1) = a.req_range("z")?;
+                        let rect = XZRect::gen(a.req_num("y")?, x0, x1, z0, z1);
+                        let radiance = a.point("radiance")?.unwrap_or(white);
+                        let is = a.point("is")?.unwrap_or(radiance);
+                        let down = match a.name("face") {
+                            None => true,
+                            Some(tok) => match tok.text {
+                                "down" => true,
+                                "up" => false,
+                                _ => return Err(tok.err(format!("unknown face `{}`, expected down or up", tok.text)))
+                            }
+                        };
+                        Box::new(RectLight{rect, radiance, is, down})
+                    }
+                    "sphere" => {
+                        let a = Attrs::parse(head, &toks[2..], &[("cen", 3), ("r", 1), ("radiance", 3), ("is", 3)])?;
+                        let radiance = a.point("radiance")?.unwrap_or(white);
+                        let is = a.point("is")?.unwrap_or(radiance);
+                        Box::new(SphereLight{cen: a.req_point("cen")?, r: a.req_positive("r")?, radiance, is})
+                    }
+                    _ => return Err(kind.err(format!("unknown light kind `{}`, expected point, directional, spot, rect or sphere", kind.text)))
+                };
+                self.lights.push(light);
+            }
+            "sphere" => {
+                let a = Attrs::parse(head, &toks[1..], &[("cen", 3), ("r", 1), ("mat", 1)])?;
+                let mat = self.material(&a)?;
+                let emission = mat.emission;
+                self.add_object(Box::new(Sphere{cen: a.req_point("cen")?, r: a.req_positive("r")?, def_color: mat.base_color, material: mat}), emission);
+            }
+            "xyrect" => {
+                let a = Attrs::parse(head, &toks[1..], &[("z", 1), ("x", 2), ("y", 2), ("mat", 1)])?;
+                let (x0, x1) = a.req_range("x")?;
+                let (y0, y1) = a.req_range("y")?;
+                let mut rect = XYRect::gen(a.req_num("z")?, x0, x1, y0, y1);
+                rect.material = self.material(&a)?;
+                let emission = rect.material.emission;
+                self.add_object(Box::new(rect), emission);
+            }
+            "xzrect" => {
+                let a = Attrs::parse(head, &toks[1..], &[("y", 1), ("x", 2), ("z", 2), ("mat", 1)])?;
+                let (x0, x1) = a.req_range("x")?;
+                let (z0, z1) = a.req_range("z")?;
+                let mut rect = XZRect::gen(a.req_num("y")?, x0, x1, z0, z1);
+                rect.material = self.material(&a)?;
+                let emission = rect.material.emission;
+                self.add_object(Box::new(rect), emission);
+            }
+            "yzrect" => {
+                let a = Attrs::parse(head, &toks[1..], &[("x", 1), ("y", 2), ("z", 2), ("mat", 1)])?;
+                let (y0, y1) = a.req_range("y")?;
+                let (z0, z1) = a.req_range("z")?;
+                let mut rect = YZRect::gen(a.req_num("x")?, y0, y1, z0, z1);
+                rect.material = self.material(&a)?;
+                let emission = rect.material.emission;
+                self.add_object(Box::new(rect), emission);
+            }
+            "triangle" => {
+                let a = Attrs::parse(head, &toks[1..], &[("v0", 3), ("v1", 3), ("v2", 3), ("mat", 1)])?;
+                let mut tri = Triangle::gen(a.req_point("v0")?, a.req_point("v1")?, a.req_point("v2")?);
+                if tri.normal().mag().is_nan() {
+                    return Err(head.err(String::from("triangle is degenerate (vertices are collinear)")));
+                }
+                tri.material = Arc::new(self.material(&a)?);
+                let emission = tri.material.emission;
+                self.add_object(Box::new(tri), emission);
+            }
+            "mesh" => {
+                let a = Attrs::parse(head, &toks[1..], &[("file", 1), ("scale", 1), ("offset", 3), ("mat", 1)])?;
+                let file = a.name("file").ok_or_else(|| a.missing("file"))?;
+                let scale = match a.num("scale")? {
+                    Some(_) => a.req_positive("scale")?,
+                    None => 1.0
+                };
+                let offset = a.point("offset")?.unwrap_or_default();
+                let mat = match a.name("mat") {
+                    Some(_) => Some(Arc::new(self.material(&a)?)),
+                    None => None
+                };
+
+                let tris = obj::load_obj(&self.base_dir.join(file.text)).map_err(|e| file.err(e.to_string()))?;
+                let mut objs: Vec<(Box<dyn Hittable>, Point)> = Vec::with_capacity(tris.len());
+                for mut tri in tris {
+                    tri.v0 = scale * tri.v0 + offset;
+                    tri.v1 = scale * tri.v1 + offset;
+                    tri.v2 = scale * tri.v2 + offset;
+                    if let Some(m) = &mat {
+                        tri.material = Arc::clone(m);
+                    }
+                    let emission = tri.material.emission;
+                    objs.push((Box::new(tri), emission));
+                }
+                self.add_objects(objs);
+            }
+            "aabox" => {
+                let a = Attrs::parse(head, &toks[1..], &[("min", 3), ("max", 3), ("mat", 1)])?;
+                let (min, max) = (a.req_point("min")?, a.req_point("max")?);
+                if !(min.x < max.x && min.y < max.y && min.z < max.z) {
+                    return Err(a.vals["max"][0].err(String::from("`max` must be greater than `min` on every axis")));
+                }
+                let mat = self.material(&a)?;
+                let emission = mat.emission;
+                self.add_object(Box::new(AABox::gen(min, max, mat)), emission);
+            }
+            "bbox" => {
+                let a = Attrs::parse(head, &toks[1..], &[("cen", 3), ("size", 3), ("mat", 1)])?;
+                let size = a.req_point("size")?;
+                if size.x <= 0.0 || size.y <= 0.0 || size.z <= 0.0 {
+                    return Err(a.vals["size"][0].err(String::from("`size` must be positive on every axis")));
+                }
+                let mut bbox = BBox::gen(a.req_point("cen")?, size.x, size.y, size.z);
+                bbox.material = self.material(&a)?;
+                let emission = bbox.material.emission;
+                self.add_object(Box::new(bbox), emission);
+            }
+            _ => return Err(head.err(format!("unknown directive `{}`", head.text)))
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,220 @@
+// Bill Derksen - 10/26
+//-- shading models: turn a ray hit into a pixel color
+
+use crate::ray::{Ray};
+use crate::vmaths::{Point, Vec3};
+use crate::hittable::{Hittable, HitInfo};
+use crate::light::{Light, LightSample};
+use crate::sampler::{Rng};
+use crate::scene::{Scene};
+use crate::film::{srgb_encode};
+
+//---- Phong Reflection / Shading Model
+//-- Phong Light Model --> illumination at point = sum of ambient, diffuse, and specular light (emission is added by trace_ray)
+//- for multiple lights, sum diffuse + specular with respect to each light
+//- area lights are averaged over AREA_SAMPLES random points on them, so their shadows come out soft
+//- eye is where the hit is seen from (camera, or the previous bounce), result is linear rgb
+pub fn phong_single_src(hit_rec: &HitInfo, eye: Point, scene: &Scene, rng: &mut Rng) -> Point{
+
+    //-- temp/test material light constants
+    //let kd = 0.3;
+    //let ks = 0.5;
+    //let alpha = 50.0;                                  //- "shininess" factor
+
+    let mat_base_color: Point = hit_rec.color();
+    
+    //-- global ambient vals + ambient light calc
+    let ia = Point{x:1.0 , y: 1.0, z: 1.0};             //- actually colors, but need to use floats
+    let ka = 0.05;
+    let ambient = ka * ia;
+
+    //-- init illumination (ambient light + base object color)
+    //let temp_color = Point::gen(0.1, 0.1, 0.1);                         //-- TODO: integrate material structures
+    let mut illu = ambient + mat_base_color;
+
+    //-- loop through lights --> calculate diffuse + specular contributions for each
+    for light in &scene.lights{
+        let samples = if light.is_delta() { 1 } else { AREA_SAMPLES };
+        let mut sum = Point::default();
+        for _ in 0..samples {
+            if let Some(sample) = light.sample_li(hit_rec.ip, rng) {
+                sum = sum + phong_light(hit_rec, eye, light.as_ref(), &sample, &scene.objects);
+            }
+        }
+        illu = illu + (1.0 / samples as f64) * sum;                                       //-- sum lights + base color of hit object
+    }
+
+    illu
+}
+
+//-- Phong shadow rays per area light at each hit
+const AREA_SAMPLES: u32 = 8;
+
+//-- diffuse (from li) + specular (from spec) for one light sample, black if the light is behind the surface or blocked
+fn phong_light(hit_rec: &HitInfo, eye: Point, light: &dyn Light, sample: &LightSample, hit_scene: &[Box<dyn Hittable>]) -> Point{
+
+    let kd = hit_rec.hit_mat.kd;
+    let ks = hit_rec.hit_mat.ks;
+    let alpha = hit_rec.hit_mat.alpha;                                  //- "shininess" factor
+
+    //-- calculate vectors for Phong model comp
+    let n: Vec3 = hit_rec.facing_norm();                //- unit normal, on the side being looked at
+    let lv: Vec3 = sample.dir;                          //- hit pt -> light
+    let rv: Vec3 = 2.0 * lv.dot(n) * n - lv;            //- perfect light reflection at hit pt
+    let cv: Vec3 = (eye - hit_rec.ip).unit();           //- hit pt -> camera "eye"
+
+    //-- shadows: no diffuse/specular if the light is behind the surface or anything sits between the hit and the light
+    if lv.dot(n) <= 0.0 || light.occluded(hit_rec.ip, n, sample, hit_scene) {
+        return Point::default();
+    }
+
+    //-- get respective light intensities (already attenuated), pdf is 1 for point-like lights
+    let id = (1.0 / sample.pdf) * sample.li;
+    let is = (1.0 / sample.pdf) * sample.spec;
+
+    //-- calc diffuse/specular light
+    let diffuse = kd * (lv.dot(n)) * id;
+    let specular = ks * (rv.dot(cv).clamp( 0.0, 1.0).powf(alpha) * is);       //-- need to clamp dot product to prevent dual specular
+
+    diffuse + specular
+}
+
+//---- Recursive ray trace: Phong at the closest hit, blended with a mirror bounce for reflective materials
+//- emission + (1 - r) * local + r * reflected: the blend is convex, so apart from its own emission a surface never
+//- returns more than the brighter of its Phong light and its mirror image, and a perfect mirror (r = 1) returns
+//- exactly what it reflects, none of its ambient or base color
+pub fn trace_ray(ray: &Ray, scene: &Scene, rng: &mut Rng, depth: u32, max_depth: u32) -> Point{
+
+    let hit_rec = match closest_hit(ray, &scene.objects) {
+        Some(hit_rec) => hit_rec,
+        None => return Point::default()                                                  //-- background: black
+    };
+    //-- clear dielectric: all of its light comes through the reflected + refracted rays
+    if let Some(ior) = hit_rec.hit_mat.ior {
+        if depth < max_depth {
+            return dielectric(&hit_rec, ray, ior, scene, rng, depth, max_depth);
+        }
+    }
+
+    let emitted = hit_rec.hit_mat.emission;
+    let local = phong_single_src(&hit_rec, ray.origin, scene, rng);
+    let r = hit_rec.hit_mat.reflectivity.clamp(0.0, 1.0);
+    if r <= 0.0 || depth >= max_depth {
+        return emitted + local;
+    }
+
+    //-- mirror direction about the normal (faced against the incoming ray), started just off the surface
+    let d: Vec3 = ray.dir.unit();
+    let n: Vec3 = hit_rec.facing_norm();
+    let refl_ray = Ray{origin: hit_rec.ip + SHADOW_EPS * n, dir: d - 2.0 * d.dot(n) * n};
+    let reflected = trace_ray(&refl_ray, scene, rng, depth + 1, max_depth);
+
+    emitted + (1.0 - r) * local + r * reflected
+}
+
+//---- Dielectric: split the ray into a reflected + refracted (Snell's law) part, weighted by Schlick's Fresnel approx.
+fn dielectric(hit_rec: &HitInfo, ray: &Ray, ior: f64, scene: &Scene, rng: &mut Rng, depth: u32, max_depth: u32) -> Point{
+
+    let split = DielectricSplit::at(ray.dir.unit(), hit_rec.norm, ior);
+
+    let refl_ray = Ray{origin: hit_rec.ip + SHADOW_EPS * split.n, dir: split.reflected};
+    let reflected = trace_ray(&refl_ray, scene, rng, depth + 1, max_depth);
+
+    //-- total internal reflection: no refracted ray
+    let refracted_dir = match split.refracted {
+        Some(dir) => dir,
+        None => return reflected
+    };
+
+    let refr_ray = Ray{origin: hit_rec.ip - SHADOW_EPS * split.n, dir: refracted_dir};
+    let refracted = trace_ray(&refr_ray, scene, rng, depth + 1, max_depth);
+
+    split.fresnel * reflected + (1.0 - split.fresnel) * refracted
+}
+
+//---- Dielectric Split: where light goes at a dielectric surface, and how much of it is reflected
+pub struct DielectricSplit{
+    pub n: Vec3,                    //-- surface normal, faced against the incoming ray
+    pub reflected: Vec3,
+    pub refracted: Option<Vec3>,    //-- None on total internal reflection
+    pub fresnel: f64                //-- fraction reflected, 1 on total internal reflection
+
+} impl DielectricSplit {
+
+    //-- d = unit incoming dir, norm = unit normal pointing out of the object
+    pub fn at(d: Vec3, norm: Vec3, ior: f64) -> DielectricSplit{
+
+        //-- entering: air -> material, exiting: material -> air (flip n to face the incoming ray)
+        let entering = d.dot(norm) < 0.0;
+        let eta = if entering { 1.0 / ior } else { ior };
+        let n = if entering { norm } else { -norm };
+
+        let cos_i = -d.dot(n);
+        let sin2_t = eta * eta * (1.0 - cos_i * cos_i);
+        let reflected = d + 2.0 * cos_i * n;
+        if sin2_t > 1.0 {
+            return DielectricSplit{n, reflected, refracted: None, fresnel: 1.0};
+        }
+
+        //-- Schlick: reflectance at normal incidence, raised toward 1 at grazing angles (on the air side)
+        let cos_t = (1.0 - sin2_t).sqrt();
+        let r0 = ((1.0 - ior) / (1.0 + ior)).powi(2);
+        let cos_air = if entering { cos_i } else { cos_t };
+        let fresnel = r0 + (1.0 - r0) * (1.0 - cos_air).powi(5);
+
+        DielectricSplit{n, reflected, refracted: Some(eta * d + (eta * cos_i - cos_t) * n), fresnel}
+    }
+}
+
+//---- Closest hit in front of the ray origin, across all scene objects, with its material's bump applied to the normal
+pub fn closest_hit<'a>(ray: &Ray, hit_scene: &'a [Box<dyn Hittable>]) -> Option<HitInfo<'a>>{
+    let mut closest: Option<HitInfo> = None;
+    let mut closest_t = f64::INFINITY;
+    for obj in hit_scene {
+        if let Some(hit_rec) = obj.hits(ray, 0.0, closest_t) {
+            closest_t = hit_rec.t;
+            closest = Some(hit_rec);
+        }
+    }
+
+    //- bump only the winner, bumping every candidate would waste texture lookups
+    if let Some(hit_rec) = &mut closest {
+        if let Some(bump) = &hit_rec.hit_mat.bump {
+            hit_rec.norm = bump.perturb(hit_rec.norm, hit_rec.uv, hit_rec.ip);
+        }
+    }
+    closest
+}
+
+//---- Shadow ray: from just off the surface (along normal n) toward a light dist away along unit dir
+//- for a finite dist the ray spans the full distance to the light, so only hits with t in (0, 1) block it
+pub fn in_shadow(ip: Point, n: Vec3, dir: Vec3, dist: f64, hit_scene: &[Box<dyn Hittable>]) -> bool{
+    let origin = ip + SHADOW_EPS * n;
+    let (shadow_ray, t_max) = if dist.is_finite() {
+        (Ray{origin, dir: (ip + dist * dir) - origin}, 1.0)
+    } else {
+        (Ray{origin, dir}, f64::INFINITY)
+    };
+    hit_scene.iter().any(|obj| obj.hits_any(&shadow_ray, 0.0, t_max))
+}
+
+//-- surface offset for secondary rays, keeps them from hitting the surface they start on
+pub const SHADOW_EPS: f64 = 1e-4;
+
+//---- Color
+#[derive(Copy, Clone, Default, PartialEq, Debug)]
+pub struct Color{
+    pub r: u8,
+    pub g: u8,
+    pub b: u8
+
+} impl Color {
+
+    //-- sRGB encode a linear display value (each channel in [0, 1], see film::ToneMap) to RGB 0-255 space
+    pub fn from_display(c: Point) -> Color{
+        let byte = |x: f64| (srgb_encode(x.clamp(0.0, 1.0)) * 255.0).round() as u8;
+        Color{r: byte(c.x), g: byte(c.y), b: byte(c.z)}
+    }
+}
+
+
@@ -0,0 +1,222 @@
+// Bill Derksen - 10/26
+//-- textures: colors that vary over a surface, looked up by (u, v) and/or the hit point
+
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::film::{srgb_decode};
+use crate::noise::{Perlin};
+use crate::vmaths::{Point, Vec3};
+
+//---- Texture: linear rgb color at surface coords uv (each nominally in [0, 1]) and world point p
+pub trait Texture: Send + Sync{
+    fn value(&self, uv: (f64, f64), p: Point) -> Point;
+
+    //-- scalar height for bump mapping, the average of the channels unless overridden
+    fn height(&self, uv: (f64, f64), p: Point) -> f64{
+        let c = self.value(uv, p);
+        (c.x + c.y + c.z) / 3.0
+    }
+}
+
+//---- Solid: the same color everywhere
+pub struct Solid{
+    pub color: Point
+
+} impl Texture for Solid {
+    fn value(&self, _uv: (f64, f64), _p: Point) -> Point{
+        self.color
+    }
+}
+
+//---- Checker: 3D checkerboard of size x size x size cells in world space, so it carves cleanly through any shape
+pub struct Checker{
+    pub even: Point,
+    pub odd: Point,
+    pub size: f64
+
+} impl Texture for Checker {
+    fn value(&self, _uv: (f64, f64), p: Point) -> Point{
+        let cell = (p.x / self.size).floor() + (p.y / self.size).floor() + (p.z / self.size).floor();
+        if cell.rem_euclid(2.0) == 0.0 { self.even } else { self.odd }
+    }
+}
+
+//---- Noise Kind: how a noise texture shapes the noise field into a blend factor in [0, 1]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum NoiseKind{
+    Perlin,             //-- a single octave, smooth blobs
+    Fbm,                //-- octaves summed, clouds
+    Turbulence,         //-- octaves of |noise|, billows and smoke
+    Marble,             //-- bands along x, warped by turbulence
+    Wood                //-- rings around the y axis, warped by fbm
+}
+
+//---- Noise Texture: procedural 3D texture that blends low -> high by a noise field in world space
+//- p is multiplied by scale before the lookup, so larger scale = finer detail
+pub struct NoiseTexture{
+    pub noise: Perlin,
+    pub kind: NoiseKind,
+    pub scale: f64,
+    pub octaves: u32,
+    pub lacunarity: f64,            //-- frequency multiplier per octave (~2)
+    pub gain: f64,                  //-- amplitude multiplier per octave (~0.5)
+    pub low: Point,
+    pub high: Point
+
+} impl NoiseTexture {
+
+    //-- grayscale fbm with the usual octave settings, deterministic from seed
+    pub fn new(kind: NoiseKind, seed: u64) -> NoiseTexture{
+        NoiseTexture{noise: Perlin::new(seed), kind, scale: 1.0, octaves: 6, lacunarity: 2.0, gain: 0.5,
+            low: Point::gen(0.0, 0.0, 0.0), high: Point::gen(1.0, 1.0, 1.0)}
+    }
+
+    //-- blend factor in [0, 1] at world point p
+    pub fn amount(&self, p: Point) -> f64{
+        let sp = self.scale * p;
+        let (n, lac, gain) = (self.octaves, self.lacunarity, self.gain);
+        let t = match self.kind {
+            NoiseKind::Perlin => 0.5 * (1.0 + self.noise.noise(sp)),
+            NoiseKind::Fbm => 0.5 * (1.0 + self.noise.fbm(sp, n, lac, gain)),
+            NoiseKind::Turbulence => self.noise.turbulence(sp, n, lac, gain),
+            NoiseKind::Marble => 0.5 * (1.0 + (sp.x + 10.0 * self.noise.turbulence(sp, n, lac, gain)).sin()),
+            NoiseKind::Wood => {
+                let rings = 4.0 * (sp.x * sp.x + sp.z * sp.z).sqrt() + 2.0 * self.noise.fbm(sp, n, lac, gain);
+                rings - rings.floor()
+            }
+        };
+        t.clamp(0.0, 1.0)
+    }
+
+} impl Texture for NoiseTexture {
+    fn value(&self, _uv: (f64, f64), p: Point) -> Point{
+        let t = self.amount(p);
+        (1.0 - t) * self.low + t * self.high
+    }
+
+    fn height(&self, _uv: (f64, f64), p: Point) -> f64{
+        self.amount(p)
+    }
+}
+
+//---- Bump: perturbs shading normals by the slope of a texture's height in world space
+//- only 3D textures (checker, noise) vary with p; uv-mapped ones give no slope here
+#[derive(Clone)]
+pub struct Bump{
+    pub tex: Arc<dyn Texture>,
+    pub strength: f64               //-- height units per world unit, 0 = flat
+
+} impl Bump {
+
+    //-- bumped unit normal for unit normal n at (uv, p)
+    pub fn perturb(&self, n: Vec3, uv: (f64, f64), p: Point) -> Vec3{
+        let h = self.tex.height(uv, p);
+        let slope = |d: Vec3| (self.tex.height(uv, p + BUMP_EPS * d) - h) / BUMP_EPS;
+        let grad = Vec3::gen(slope(Vec3::gen(1.0, 0.0, 0.0)), slope(Vec3::gen(0.0, 1.0, 0.0)), slope(Vec3::gen(0.0, 0.0, 1.0)));
+
+        //- only the part of the gradient along the surface tilts the normal
+        let along = grad - grad.dot(n) * n;
+        let bumped = n - self.strength * along;
+        if bumped.mag() > 0.0 { bumped.unit() } else { n }
+    }
+}
+
+//-- finite difference step for bump slopes
+const BUMP_EPS: f64 = 1e-4;
+
+//---- Wrap Mode: what image lookups do outside [0, 1]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum WrapMode{
+    Repeat,             //-- tile
+    Mirror,             //-- tile, flipping every other copy
+    Clamp               //-- stretch the edge pixels
+}
+
+//---- Image Texture: a decoded PNG, sampled with bilinear filtering
+//- u runs left -> right, v bottom -> top, like most modelling tools
+pub struct ImageTexture{
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Point>,         //-- row-major from the top row, linear rgb in [0, 1]
+    pub wrap: WrapMode
+
+} impl ImageTexture {
+
+    //-- decode any 8 or 16 bit PNG (gray, rgb, palette, with or without alpha); alpha is ignored
+    //- pixels are taken as sRGB encoded (as image files almost always are) and converted to linear
+    pub fn load(path: &Path, wrap: WrapMode) -> Result<ImageTexture, TextureError>{
+        let err = |msg: String| TextureError{path: path.to_path_buf(), msg};
+
+        let file = File::open(path).map_err(|e| err(e.to_string()))?;
+        let mut decoder = png::Decoder::new(file);
+        decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+        let (info, mut reader) = decoder.read_info().map_err(|e| err(e.to_string()))?;
+        let mut buf = vec![0; info.buffer_size()];
+        reader.next_frame(&mut buf).map_err(|e| err(e.to_string()))?;
+
+        let (width, height) = (info.width as usize, info.height as usize);
+        let channels = info.line_size / width;
+        let mut pixels = Vec::with_capacity(width * height);
+        for y in 0..height {
+            let row = &buf[y * info.line_size..];
+            for x in 0..width {
+                let px = &row[x * channels..x * channels + channels];
+                let (r, g, b) = if channels >= 3 { (px[0], px[1], px[2]) } else { (px[0], px[0], px[0]) };
+                let linear = |c: u8| srgb_decode(c as f64 / 255.0);
+                pixels.push(Point::gen(linear(r), linear(g), linear(b)));
+            }
+        }
+        Ok(ImageTexture{width, height, pixels, wrap})
+    }
+
+    //-- pixel (x, y) with x, y wrapped onto the image
+    fn texel(&self, x: i64, y: i64) -> Point{
+        let x = wrap_index(x, self.width, self.wrap);
+        let y = wrap_index(y, self.height, self.wrap);
+        self.pixels[y * self.width + x]
+    }
+
+} impl Texture for ImageTexture {
+
+    //-- blend the 4 pixels around the lookup point, pixel centers sit at half-integer coords
+    fn value(&self, uv: (f64, f64), _p: Point) -> Point{
+        let x = uv.0 * self.width as f64 - 0.5;
+        let y = (1.0 - uv.1) * self.height as f64 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let top = (1.0 - fx) * self.texel(x0, y0) + fx * self.texel(x0 + 1, y0);
+        let bottom = (1.0 - fx) * self.texel(x0, y0 + 1) + fx * self.texel(x0 + 1, y0 + 1);
+        (1.0 - fy) * top + fy * bottom
+    }
+}
+
+//-- index i into 0..n per the wrap mode
+fn wrap_index(i: i64, n: usize, wrap: WrapMode) -> usize{
+    let n = n as i64;
+    match wrap {
+        WrapMode::Repeat => i.rem_euclid(n) as usize,
+        WrapMode::Mirror => {
+            let m = i.rem_euclid(2 * n);
+            (if m < n { m } else { 2 * n - 1 - m }) as usize
+        }
+        WrapMode::Clamp => i.clamp(0, n - 1) as usize
+    }
+}
+
+//---- Texture Error: an image that could not be read or decoded
+#[derive(Debug)]
+pub struct TextureError{
+    pub path: PathBuf,
+    pub msg: String
+
+} impl fmt::Display for TextureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result{
+        write!(f, "could not load texture {}: {}", self.path.display(), self.msg)
+    }
+} impl Error for TextureError {}
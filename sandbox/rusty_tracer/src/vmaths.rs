@@ -23,7 +23,7 @@ impl Point{
     }
 
     pub fn mag(&self) -> f64{
-      (&self.x.powf(2.0) + &self.y.powf(2.0) + &self.z.powf(2.0)).abs().sqrt()
+      (self.x.powf(2.0) + self.y.powf(2.0) + self.z.powf(2.0)).abs().sqrt()
     }
 
     pub fn unit(&self) -> Vec3{
@@ -31,44 +31,73 @@ impl Point{
     }
 
     pub fn dot(&self, vec: Vec3) -> f64{
-        (&self.x * &vec.x + &self.y * &vec.y + &self.z * &vec.z)
+        self.x * vec.x + self.y * vec.y + self.z * vec.z
+    }
+
+    //-- component-wise min/max
+    pub fn min(&self, vec: Vec3) -> Vec3{
+        Vec3{x: self.x.min(vec.x), y: self.y.min(vec.y), z: self.z.min(vec.z)}
+    }
+
+    pub fn max(&self, vec: Vec3) -> Vec3{
+        Vec3{x: self.x.max(vec.x), y: self.y.max(vec.y), z: self.z.max(vec.z)}
+    }
+
+    //-- component-wise product, e.g. filtering light by a surface color
+    pub fn mul_elem(&self, vec: Vec3) -> Vec3{
+        Vec3{x: self.x * vec.x, y: self.y * vec.y, z: self.z * vec.z}
+    }
+
+    pub fn max_elem(&self) -> f64{
+        self.x.max(self.y).max(self.z)
     }
 
     pub fn cross(&self, vec: Vec3) -> Vec3{
-        Vec3{x: (&self.y * vec.z) - (&self.z * vec.y), y: (&self.x * vec.z) - (&self.z * vec.x) , z: (&self.x * vec.y) - (&self.y * vec.x) }
+        Vec3{x: (self.y * vec.z) - (self.z * vec.y), y: (self.z * vec.x) - (self.x * vec.z) , z: (self.x * vec.y) - (self.y * vec.x) }
     }
 
 } impl Stringable for Point {
     fn stringy(&self) -> String{
-        return String::from("<".to_owned() + &self.x.to_string() + ", "+ &self.y.to_string() + ", "+ &self.z.to_string() + ">");  
+        "<".to_owned() + &self.x.to_string() + ", "+ &self.y.to_string() + ", "+ &self.z.to_string() + ">"  
     }
 
 } impl ops::Add for Vec3 {            //-- Add overload
     type Output = Vec3;
 
     fn add(self, vec: Vec3) -> Vec3 {
-        Vec3 { x: &self.x + &vec.x, y: &self.y + &vec.y, z: &self.z + &vec.z }
+        Vec3 { x: self.x + vec.x, y: self.y + vec.y, z: self.z + vec.z }
     }
 
 } impl ops::Sub for Vec3 {          //-- Sub - overload
     type Output = Vec3;
 
     fn sub(self, vec: Vec3) -> Vec3 {
-        Vec3 { x: &self.x - &vec.x, y: &self.y - &vec.y, z: &self.z - &vec.z }
+        Vec3 { x: self.x - vec.x, y: self.y - vec.y, z: self.z - vec.z }
     }
 
 } impl ops::Mul<f64> for Vec3 {     //-- SCALAR Mult * overload
     type Output = Vec3;
 
     fn mul(self, s: f64) -> Vec3 {
-        Vec3 { x: &self.x * s, y: &self.y * s, z: &self.z * s }
+        Vec3 { x: self.x * s, y: self.y * s, z: self.z * s }
     }
 
 } impl ops::Neg for Vec3 {          //-- Neg -x overload
     type Output = Vec3;
 
     fn neg(self) -> Vec3 {
-        Vec3 { x: -&self.x, y: -&self.y, z: -&self.z}
+        Vec3 { x: -self.x, y: -self.y, z: -self.z}
+    }
+} impl ops::Index<usize> for Vec3 {   //-- axis access, 0 = x, 1 = y, 2 = z
+    type Output = f64;
+
+    fn index(&self, axis: usize) -> &f64 {
+        match axis {
+            0 => &self.x,
+            1 => &self.y,
+            2 => &self.z,
+            _ => panic!("Vec3 axis out of range: {}", axis)
+        }
     }
 } impl ops::Mul<Vec3> for f64{
     type Output = Vec3;
@@ -104,7 +133,7 @@ pub struct Mat3{
 } impl Stringable for Mat3{
 
     fn stringy(&self) -> String{
-        return String::from(self.x.stringy().to_owned() + "\n" + &self.y.stringy() + "\n" + &self.z.stringy());
+        self.x.stringy() + "\n" + &self.y.stringy() + "\n" + &self.z.stringy()
     }
 
 } impl ops::Mul<Vec3> for Mat3{     //-- row major mat3 x vec3...
@@ -0,0 +1,80 @@
+// Bill Derksen - 10/26
+//-- bvh vs brute force tests: the tree must find exactly what testing every object would
+
+use rusty_tracer::bvh::{Bvh};
+use rusty_tracer::geometry::{AABox, Triangle};
+use rusty_tracer::hittable::{Hittable};
+use rusty_tracer::material::{Material};
+use rusty_tracer::ray::{Ray};
+use rusty_tracer::stringable::{Stringable};
+use rusty_tracer::vmaths::{Point, Vec3};
+
+const EPS: f64 = 1e-9;
+
+fn p(x: f64, y: f64, z: f64) -> Point{
+    Point::gen(x, y, z)
+}
+
+//-- shapes whose bounds sit on whole and half coords, so axis-parallel rays from the grid below run along box planes
+fn objects() -> Vec<Box<dyn Hittable>>{
+    let mut objects: Vec<Box<dyn Hittable>> = Vec::new();
+    for i in 0..4 {
+        let x = i as f64;
+        objects.push(Box::new(Triangle::gen(p(x, 0.0, 1.0), p(x + 1.0, 0.0, 1.0), p(x, 1.0, 1.0))));
+        objects.push(Box::new(Triangle::gen(p(x, 1.0, 2.0 + x), p(x + 1.0, 1.0, 2.0 + x), p(x + 1.0, 2.0, 2.0 + x))));
+    }
+    objects.push(Box::new(AABox::gen(p(0.0, 0.0, 4.0), p(1.5, 2.0, 5.0), Material::default())));
+    objects.push(Box::new(AABox::gen(p(2.0, 0.5, 6.0), p(3.0, 1.0, 7.0), Material::default())));
+    objects
+}
+
+//-- rays along +-x, +-y and +-z starting on the coordinate grid from outside the shapes
+fn axis_rays() -> Vec<Ray>{
+    let coords = [0.0, 0.5, 1.0, 1.5, 2.0, 3.0, 4.0];
+    let mut rays = Vec::new();
+    for &a in &coords {
+        for &b in &coords {
+            for s in [1.0, -1.0] {
+                rays.push(Ray{origin: p(a, b, -5.0 * s + 4.0), dir: p(0.0, 0.0, s)});
+                rays.push(Ray{origin: p(-5.0 * s + 2.0, a, b), dir: p(s, 0.0, 0.0)});
+                rays.push(Ray{origin: p(a, -5.0 * s + 1.0, b), dir: p(0.0, s, 0.0)});
+            }
+        }
+    }
+    rays
+}
+
+fn brute_force_t(objects: &[Box<dyn Hittable>], ray: &Ray) -> Option<f64>{
+    objects.iter().filter_map(|o| o.hits(ray, 0.0, f64::INFINITY)).map(|h| h.t).reduce(f64::min)
+}
+
+#[test]
+fn axis_parallel_rays_on_box_planes_match_brute_force(){
+    let bvh = Bvh::build(objects());
+    let flat = objects();
+    let mut hits = 0;
+    for ray in axis_rays() {
+        let expected = brute_force_t(&flat, &ray);
+        let got = bvh.hits(&ray, 0.0, f64::INFINITY).map(|h| h.t);
+        match (expected, got) {
+            (Some(e), Some(g)) => assert!((e - g).abs() < EPS, "ray from {} along {}: expected t {}, got {}", ray.origin.stringy(), ray.dir.stringy(), e, g),
+            (None, None) => {}
+            _ => panic!("ray from {} along {}: expected {:?}, got {:?}", ray.origin.stringy(), ray.dir.stringy(), expected, got)
+        }
+        assert_eq!(bvh.hits_any(&ray, 0.0, f64::INFINITY), expected.is_some(), "hits_any, ray from {} along {}", ray.origin.stringy(), ray.dir.stringy());
+        hits += expected.is_some() as u32;
+    }
+    assert!(hits > 0, "test rays should hit something");
+}
+
+#[test]
+fn ray_on_triangle_edge_plane_hits_through_bvh(){
+    let tri = Triangle::gen(p(0.0, 0.0, 1.0), p(1.0, 0.0, 1.0), p(0.0, 1.0, 1.0));
+    let ray = Ray{origin: p(0.0, 0.25, -1.0), dir: Vec3::gen(0.0, 0.0, 1.0)};
+    assert!(tri.hits(&ray, 0.0, f64::INFINITY).is_some());
+
+    let bvh = Bvh::build(vec![Box::new(tri)]);
+    let hit = bvh.hits(&ray, 0.0, f64::INFINITY).expect("bvh should find the triangle");
+    assert!((hit.t - 2.0).abs() < EPS);
+    assert!(bvh.hits_any(&ray, 0.0, f64::INFINITY));
+}
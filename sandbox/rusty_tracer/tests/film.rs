@@ -0,0 +1,127 @@
+// Bill Derksen - 10/26
+//-- film tests: tone map curves, exposure, and the sRGB transfer curve
+
+use rusty_tracer::film::{self, Film, ToneMap};
+use rusty_tracer::vmaths::{Point};
+
+const EPS: f64 = 1e-9;
+
+fn gray(v: f64) -> Point{
+    Point::gen(v, v, v)
+}
+
+//-- tone map applied to one channel
+fn curve(tone_map: ToneMap, x: f64) -> f64{
+    tone_map.apply(gray(x)).x
+}
+
+fn assert_close(a: f64, b: f64, eps: f64){
+    assert!((a - b).abs() < eps, "expected {}, got {}", b, a);
+}
+
+//-- 0 to 100, dense near 0 where the curves bend most
+fn ramp() -> Vec<f64>{
+    (0..=2000).map(|i| 100.0 * (i as f64 / 2000.0).powi(3)).collect()
+}
+
+#[test]
+fn reinhard_is_half_at_one(){
+    assert_close(curve(ToneMap::Reinhard, 1.0), 0.5, EPS);
+    assert_close(curve(ToneMap::Reinhard, 3.0), 0.75, EPS);
+    assert_close(curve(ToneMap::Reinhard, 0.0), 0.0, EPS);
+    assert!(curve(ToneMap::Reinhard, 1e6) < 1.0);
+}
+
+#[test]
+fn aces_is_monotonic_and_bounded(){
+    let mut last = -1.0;
+    for x in ramp() {
+        let y = curve(ToneMap::Aces, x);
+        assert!((0.0..=1.0).contains(&y), "aces({}) = {} is outside [0, 1]", x, y);
+        assert!(y >= last, "aces falls from {} to {} at {}", last, y, x);
+        last = y;
+    }
+    assert_close(curve(ToneMap::Aces, 0.0), 0.0, EPS);
+    assert_close(curve(ToneMap::Aces, 1e9), 1.0, EPS);
+}
+
+#[test]
+fn every_curve_maps_bad_input_to_black_and_infinity_to_white(){
+    for tone_map in [ToneMap::Clamp, ToneMap::Reinhard, ToneMap::Aces] {
+        let c = tone_map.apply(Point::gen(-2.0, f64::NAN, f64::NEG_INFINITY));
+        assert_eq!((c.x, c.y, c.z), (0.0, 0.0, 0.0), "{:?}", tone_map);
+        assert_close(curve(tone_map, f64::INFINITY), 1.0, 1e-9);
+    }
+}
+
+#[test]
+fn clamp_cuts_off_at_one(){
+    assert_close(curve(ToneMap::Clamp, 0.25), 0.25, EPS);
+    assert_close(curve(ToneMap::Clamp, 1.0), 1.0, EPS);
+    assert_close(curve(ToneMap::Clamp, 7.5), 1.0, EPS);
+}
+
+#[test]
+fn tone_maps_by_name(){
+    for name in ToneMap::NAMES {
+        assert!(ToneMap::by_name(name).is_some(), "{}", name);
+    }
+    assert_eq!(ToneMap::by_name("aces"), Some(ToneMap::Aces));
+    assert_eq!(ToneMap::by_name("filmic"), None);
+}
+
+#[test]
+fn srgb_round_trips(){
+    //- both sides of the linear segment (0.0031308 linear, 0.04045 encoded) and its edge, where the standard's
+    //- rounded constants leave the two pieces ~3e-8 apart
+    let xs = [0.0, 1e-5, 0.001, 0.003, 0.0031308, 0.0031309, 0.0032, 0.01, 0.04045, 0.2, 0.5, 0.9, 1.0];
+    for x in xs {
+        assert_close(film::srgb_encode(film::srgb_decode(x)), x, 1e-7);
+        assert_close(film::srgb_decode(film::srgb_encode(x)), x, 1e-7);
+    }
+    assert_close(film::srgb_encode(0.001), 0.01292, 1e-12);
+    assert_close(film::srgb_encode(1.0), 1.0, 1e-12);
+    assert_close(film::srgb_encode(0.5), 0.735356983, 1e-6);
+}
+
+#[test]
+fn srgb_segments_meet(){
+    //- the two pieces of each curve agree where they switch over, to far below an 8 bit step
+    let (lo, hi) = (film::srgb_encode(0.0031308), film::srgb_encode(0.0031308 + 1e-12));
+    assert_close(hi, lo, 1e-7);
+    let (lo, hi) = (film::srgb_decode(0.04045), film::srgb_decode(0.04045 + 1e-12));
+    assert_close(hi, lo, 1e-7);
+}
+
+#[test]
+fn exposure_scales_by_stops(){
+    let mut film = Film::new(3, 1);
+    film.set(0, 0, gray(0.05));
+    film.set(1, 0, gray(0.2));
+    film.set(2, 0, Point::gen(0.01, 0.1, 0.3));
+
+    let mut doubled = Film::new(3, 1);
+    for x in 0..3 {
+        doubled.set(x, 0, 2.0 * film.get(x, 0));
+    }
+    let mut quartered = Film::new(3, 1);
+    for x in 0..3 {
+        quartered.set(x, 0, 0.25 * film.get(x, 0));
+    }
+
+    for tone_map in [ToneMap::Clamp, ToneMap::Reinhard, ToneMap::Aces] {
+        let bytes = |f: &Film, stops: f64| -> Vec<(u8, u8, u8)> { f.develop(tone_map, stops).iter().map(|c| (c.r, c.g, c.b)).collect() };
+        assert_eq!(bytes(&film, 1.0), bytes(&doubled, 0.0), "{:?} +1 stop", tone_map);
+        assert_eq!(bytes(&film, -2.0), bytes(&quartered, 0.0), "{:?} -2 stops", tone_map);
+    }
+}
+
+#[test]
+fn develop_encodes_srgb(){
+    let mut film = Film::new(4, 1);
+    for (x, v) in [0.0, 0.2, 0.5, 1.0].iter().enumerate() {
+        film.set(x as u32, 0, gray(*v));
+    }
+    let out: Vec<u8> = film.develop(ToneMap::Clamp, 0.0).iter().map(|c| c.r).collect();
+    assert_eq!(out, vec![0, 124, 188, 255]);
+}
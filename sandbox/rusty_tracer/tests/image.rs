@@ -0,0 +1,125 @@
+// Bill Derksen - 10/26
+//-- image file tests: the bytes written for each format, and which format an extension picks
+
+use std::path::Path;
+
+use rusty_tracer::film::{Film, ToneMap};
+use rusty_tracer::image::{self, ImageFormat};
+use rusty_tracer::vmaths::{Point};
+
+fn p(x: f64, y: f64, z: f64) -> Point{
+    Point::gen(x, y, z)
+}
+
+//-- 2 x 2 film, every channel different so rows and columns can't be mixed up unnoticed
+fn film_2x2() -> Film{
+    let mut film = Film::new(2, 2);
+    film.set(0, 0, p(1.0, 2.0, 3.0));
+    film.set(1, 0, p(4.0, 5.0, 6.0));
+    film.set(0, 1, p(0.5, 0.25, 0.125));
+    film.set(1, 1, p(-1.0, 0.0, 1024.0));
+    film
+}
+
+fn write(format: ImageFormat, film: &Film) -> Vec<u8>{
+    let mut out = Vec::new();
+    image::write(&mut out, format, film, ToneMap::Clamp, 0.0).expect("writing to a Vec can't fail");
+    out
+}
+
+//-- value an rgbe pixel decodes to, by the Radiance reader's (byte + 0.5) / 256 * 2^(e - 128)
+fn decode_rgbe(b: [u8; 4]) -> [f64; 3]{
+    if b[3] == 0 {
+        return [0.0; 3];
+    }
+    let scale = 2f64.powi(b[3] as i32 - 128) / 256.0;
+    [(b[0] as f64 + 0.5) * scale, (b[1] as f64 + 0.5) * scale, (b[2] as f64 + 0.5) * scale]
+}
+
+#[test]
+fn rgbe_of_known_colors(){
+    assert_eq!(image::rgbe(p(1.0, 0.5, 0.25)), [128, 64, 32, 129]);
+    assert_eq!(image::rgbe(p(0.5, 0.5, 0.5)), [128, 128, 128, 128]);
+    assert_eq!(image::rgbe(p(1000.0, 0.0, 1.0)), [250, 0, 0, 138]);
+}
+
+#[test]
+fn rgbe_of_zero_and_no_light(){
+    assert_eq!(image::rgbe(p(0.0, 0.0, 0.0)), [0, 0, 0, 0]);
+    assert_eq!(image::rgbe(p(-1.0, f64::NAN, 0.0)), [0, 0, 0, 0]);
+    assert_eq!(image::rgbe(p(-1.0, 0.5, f64::NAN)), [0, 128, 0, 128]);
+}
+
+#[test]
+fn rgbe_round_trips_within_a_mantissa_step(){
+    for c in [p(0.3, 0.6, 0.9), p(12.5, 3.0, 0.01), p(1e-3, 2e-3, 4e-3), p(65000.0, 1.0, 7.0)] {
+        let v = c.x.max(c.y).max(c.z);
+        let back = decode_rgbe(image::rgbe(c));
+        for (orig, dec) in [c.x, c.y, c.z].iter().zip(back) {
+            assert!((orig - dec).abs() <= v / 256.0, "{} came back as {}", orig, dec);
+        }
+    }
+}
+
+#[test]
+fn hdr_header_then_rows_top_down(){
+    let film = film_2x2();
+    let bytes = write(ImageFormat::Hdr, &film);
+    let header = b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 2 +X 2\n";
+    assert_eq!(&bytes[..header.len()], header);
+
+    let body = &bytes[header.len()..];
+    assert_eq!(body.len(), 4 * 4);
+    for (i, px) in film.pixels.iter().enumerate() {
+        assert_eq!(body[4 * i..4 * i + 4], image::rgbe(*px), "pixel {}", i);
+    }
+}
+
+#[test]
+fn pfm_header_little_endian_rows_bottom_up(){
+    let film = film_2x2();
+    let bytes = write(ImageFormat::Pfm, &film);
+    let header = b"PF\n2 2\n-1.0\n";
+    assert_eq!(&bytes[..header.len()], header);
+
+    let body = &bytes[header.len()..];
+    assert_eq!(body.len(), 4 * 3 * 4);
+    let floats: Vec<f32> = body.chunks(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect();
+    assert_eq!(floats, vec![
+        0.5, 0.25, 0.125,   -1.0, 0.0, 1024.0,      //-- bottom row (y = 1) first
+        1.0, 2.0, 3.0,      4.0, 5.0, 6.0]);
+    assert_eq!(&body[..4], &[0x00, 0x00, 0x00, 0x3f]);     //-- 0.5f32, least significant byte first
+}
+
+#[test]
+fn png_is_the_developed_film(){
+    let film = film_2x2();
+    let bytes = write(ImageFormat::Png, &film);
+    assert_eq!(&bytes[..8], b"\x89PNG\r\n\x1a\n");
+
+    let (info, mut reader) = png::Decoder::new(&bytes[..]).read_info().expect("should decode");
+    assert_eq!((info.width, info.height), (2, 2));
+    let mut buf = vec![0; info.buffer_size()];
+    reader.next_frame(&mut buf).unwrap();
+    let developed: Vec<u8> = film.develop(ToneMap::Clamp, 0.0).iter().flat_map(|c| [c.r, c.g, c.b]).collect();
+    assert_eq!(buf, developed);
+}
+
+#[test]
+fn format_from_extension(){
+    for (path, format) in [("out.png", ImageFormat::Png), ("renders/out.hdr", ImageFormat::Hdr), ("a.b.pfm", ImageFormat::Pfm),
+                           ("LOUD.PNG", ImageFormat::Png), ("mixed.Hdr", ImageFormat::Hdr)] {
+        assert_eq!(ImageFormat::from_path(Path::new(path)), Some(format), "{}", path);
+    }
+    for path in ["out.jpg", "out.exr", "out", "png", "out.png.bak", ""] {
+        assert_eq!(ImageFormat::from_path(Path::new(path)), None, "{}", path);
+    }
+}
+
+#[test]
+fn write_dispatches_on_format(){
+    let film = film_2x2();
+    assert!(write(ImageFormat::Png, &film).starts_with(b"\x89PNG"));
+    assert!(write(ImageFormat::Hdr, &film).starts_with(b"#?RADIANCE\n"));
+    assert!(write(ImageFormat::Pfm, &film).starts_with(b"PF\n"));
+}
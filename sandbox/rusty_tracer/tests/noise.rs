@@ -0,0 +1,60 @@
+// Bill Derksen - 10/26
+//-- Perlin noise tests: a seed always gives the same field, and different seeds different ones
+
+use rusty_tracer::noise::{Perlin};
+use rusty_tracer::texture::{NoiseKind, NoiseTexture, Texture};
+use rusty_tracer::vmaths::{Point};
+
+//-- off-lattice sample points (noise is 0 on every lattice point whatever the seed)
+fn points() -> Vec<Point>{
+    (0..64).map(|i| {
+        let i = i as f64;
+        Point::gen(0.37 * i - 5.1, 1.13 * i.sin() + 0.29, 0.71 * i.cos() * 3.0 - 0.45)
+    }).collect()
+}
+
+fn field(perlin: &Perlin) -> Vec<f64>{
+    points().into_iter().flat_map(|p| [perlin.noise(p), perlin.fbm(p, 6, 2.0, 0.5), perlin.turbulence(p, 4, 2.0, 0.5)]).collect()
+}
+
+#[test]
+fn same_seed_same_noise(){
+    for seed in [0, 7, 123_456_789] {
+        let (a, b) = (field(&Perlin::new(seed)), field(&Perlin::new(seed)));
+        assert!(a.iter().zip(&b).all(|(x, y)| x.to_bits() == y.to_bits()), "seed {} gave two different fields", seed);
+    }
+}
+
+#[test]
+fn different_seed_different_noise(){
+    let base = field(&Perlin::new(7));
+    for seed in [0, 8, 1 << 40] {
+        let other = field(&Perlin::new(seed));
+        let differ = base.iter().zip(&other).filter(|(x, y)| (*x - *y).abs() > 1e-6).count();
+        assert!(differ > base.len() / 2, "seed {} only changed {} of {} samples", seed, differ, base.len());
+    }
+}
+
+#[test]
+fn noise_texture_follows_its_seed(){
+    let (a, b, c) = (NoiseTexture::new(NoiseKind::Fbm, 3), NoiseTexture::new(NoiseKind::Fbm, 3), NoiseTexture::new(NoiseKind::Fbm, 4));
+    let mut differ = 0;
+    for p in points() {
+        let (va, vb, vc) = (a.value((0.0, 0.0), p), b.value((0.0, 0.0), p), c.value((0.0, 0.0), p));
+        assert!((va - vb).mag() == 0.0);
+        if (va - vc).mag() > 1e-6 {
+            differ += 1;
+        }
+    }
+    assert!(differ > 32, "seeds 3 and 4 only differ at {} of 64 points", differ);
+}
+
+#[test]
+fn noise_stays_in_range(){
+    let perlin = Perlin::new(11);
+    for p in points() {
+        assert!(perlin.noise(p).abs() <= 1.0 + 1e-9);
+        assert!(perlin.fbm(p, 8, 2.0, 0.5).abs() <= 1.0 + 1e-9);
+        assert!((0.0..=1.0 + 1e-9).contains(&perlin.turbulence(p, 8, 2.0, 0.5)));
+    }
+}
@@ -0,0 +1,191 @@
+// Bill Derksen - 10/26
+//-- OBJ/MTL loader tests: triangulation, index handling, material mapping and errors for bad files
+
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+use std::sync::Arc;
+
+use rusty_tracer::geometry::{Triangle};
+use rusty_tracer::obj::{self, ObjError};
+use rusty_tracer::vmaths::{Point};
+
+const EPS: f64 = 1e-9;
+
+fn p(x: f64, y: f64, z: f64) -> Point{
+    Point::gen(x, y, z)
+}
+
+fn assert_point(a: Point, b: Point){
+    assert!((a - b).mag() < EPS, "expected <{}, {}, {}>, got <{}, {}, {}>", b.x, b.y, b.z, a.x, a.y, a.z);
+}
+
+//-- write files into a fresh temp folder for this test, return the path of the first
+fn write_files(test: &str, files: &[(&str, &str)]) -> PathBuf{
+    let dir = std::env::temp_dir().join(format!("rusty_tracer_obj_{}_{}", test, process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    for (name, src) in files {
+        fs::write(dir.join(name), src).unwrap();
+    }
+    dir.join(files[0].0)
+}
+
+fn load(test: &str, src: &str) -> Result<Vec<Triangle>, ObjError>{
+    obj::load_obj(&write_files(test, &[("mesh.obj", src)]))
+}
+
+//-- expect a parse error, return (line, msg)
+fn parse_err(test: &str, src: &str) -> (usize, String){
+    match load(test, src) {
+        Err(ObjError::Parse{line, msg, ..}) => (line, msg),
+        Err(e) => panic!("expected a parse error, got {}", e),
+        Ok(tris) => panic!("expected a parse error, got {} triangles", tris.len())
+    }
+}
+
+const QUAD: &str = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n";
+
+#[test]
+fn triangle_face(){
+    let tris = load("tri", "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n").unwrap();
+    assert_eq!(tris.len(), 1);
+    assert_point(tris[0].v0, p(0.0, 0.0, 0.0));
+    assert_point(tris[0].v1, p(1.0, 0.0, 0.0));
+    assert_point(tris[0].v2, p(0.0, 1.0, 0.0));
+}
+
+#[test]
+fn quad_is_fan_triangulated(){
+    let tris = load("quad", &(QUAD.to_owned() + "f 1 2 3 4\n")).unwrap();
+    assert_eq!(tris.len(), 2);
+    assert_point(tris[0].v0, p(0.0, 0.0, 0.0));
+    assert_point(tris[0].v2, p(1.0, 1.0, 0.0));
+    assert_point(tris[1].v0, p(0.0, 0.0, 0.0));
+    assert_point(tris[1].v1, p(1.0, 1.0, 0.0));
+    assert_point(tris[1].v2, p(0.0, 1.0, 0.0));
+    let area: f64 = tris.iter().map(|t| 0.5 * (t.v1 - t.v0).cross(t.v2 - t.v0).mag()).sum();
+    assert!((area - 1.0).abs() < EPS);
+}
+
+#[test]
+fn ngon_is_fan_triangulated(){
+    let src = "v 0 0 0\nv 2 0 0\nv 3 1 0\nv 1 2 0\nv -1 1 0\nv -1 0.5 0\nf 1 2 3 4 5 6\n";
+    let tris = load("ngon", src).unwrap();
+    assert_eq!(tris.len(), 4);
+    assert!(tris.iter().all(|t| (t.v0 - p(0.0, 0.0, 0.0)).mag() < EPS));
+}
+
+#[test]
+fn negative_indices_count_back(){
+    let tris = load("neg", &(QUAD.to_owned() + "f -3 -2 -1\n")).unwrap();
+    assert_eq!(tris.len(), 1);
+    assert_point(tris[0].v0, p(1.0, 0.0, 0.0));
+    assert_point(tris[0].v2, p(0.0, 1.0, 0.0));
+}
+
+#[test]
+fn out_of_range_indices_are_errors(){
+    for (test, face) in [("oob_high", "f 1 2 5"), ("oob_neg", "f -1 -2 -5"), ("oob_zero", "f 0 1 2")] {
+        let (line, msg) = parse_err(test, &(QUAD.to_owned() + face + "\n"));
+        assert_eq!(line, 5, "{}", face);
+        assert!(msg.contains("out of range"), "{}: {}", face, msg);
+    }
+}
+
+#[test]
+fn index_before_vertex_is_defined_is_an_error(){
+    let (line, msg) = parse_err("forward", "v 0 0 0\nv 1 0 0\nf 1 2 3\nv 0 1 0\n");
+    assert_eq!(line, 3);
+    assert!(msg.contains("out of range"), "{}", msg);
+}
+
+#[test]
+fn bad_uv_and_normal_indices_are_errors(){
+    let (line, msg) = parse_err("bad_vt", &(QUAD.to_owned() + "vt 0 0\nf 1/1 2/2 3/1\n"));
+    assert_eq!(line, 6);
+    assert!(msg.contains("texture coord"), "{}", msg);
+    let (_, msg) = parse_err("bad_vn", &(QUAD.to_owned() + "f 1//x 2//1 3//1\n"));
+    assert!(msg.contains("normal"), "{}", msg);
+}
+
+#[test]
+fn face_with_too_few_vertices(){
+    let (line, msg) = parse_err("short_face", &(QUAD.to_owned() + "\nf 1 2\n"));
+    assert_eq!(line, 6);
+    assert!(msg.contains("at least 3"), "{}", msg);
+}
+
+#[test]
+fn non_numeric_vertex(){
+    let (line, msg) = parse_err("bad_v", "v 0 0 0\nv 1 two 0\n");
+    assert_eq!(line, 2);
+    assert!(msg.contains("two"), "{}", msg);
+    let (line, _) = parse_err("short_v", "v 0 0\n");
+    assert_eq!(line, 1);
+}
+
+#[test]
+fn missing_mtl_file(){
+    match load("no_mtl", "mtllib nope.mtl\nv 0 0 0\n") {
+        Err(ObjError::Io{path, ..}) => assert!(path.ends_with("nope.mtl"), "{}", path.display()),
+        Err(e) => panic!("expected an io error, got {}", e),
+        Ok(_) => panic!("expected an io error")
+    }
+}
+
+#[test]
+fn missing_obj_file(){
+    let path = std::env::temp_dir().join("rusty_tracer_obj_does_not_exist.obj");
+    assert!(matches!(obj::load_obj(&path), Err(ObjError::Io{..})));
+}
+
+#[test]
+fn unknown_usemtl_is_an_error(){
+    let (line, msg) = parse_err("no_usemtl", &(QUAD.to_owned() + "usemtl gold\n"));
+    assert_eq!(line, 5);
+    assert!(msg.contains("gold"), "{}", msg);
+}
+
+#[test]
+fn mtl_maps_onto_material(){
+    let mtl = "newmtl clay\nKd 0.2 0.4 0.8\nKs 0.3 0.3 0.6\nNs 25\nKe 0 1 2\n";
+    let obj_src = "mtllib clay.mtl\n".to_owned() + QUAD + "usemtl clay\nf 1 2 3 4\n";
+    let tris = obj::load_obj(&write_files("mtl", &[("mesh.obj", &obj_src), ("clay.mtl", mtl)])).unwrap();
+    assert_eq!(tris.len(), 2);
+
+    let mat = &tris[0].material;
+    assert_eq!(mat.desc, "clay");
+    assert_point(mat.base_color, p(0.2, 0.4, 0.8));
+    assert!((mat.kd - 0.8).abs() < EPS);
+    assert!((mat.ks - 0.4).abs() < EPS);
+    assert!((mat.alpha - 25.0).abs() < EPS);
+    assert_point(mat.emission, p(0.0, 1.0, 2.0));
+
+    //- every face of the material shares one copy
+    assert!(Arc::ptr_eq(&tris[0].material, &tris[1].material));
+}
+
+#[test]
+fn bad_mtl_line_is_an_error(){
+    let obj_src = "mtllib bad.mtl\n";
+    match obj::load_obj(&write_files("bad_mtl", &[("mesh.obj", obj_src), ("bad.mtl", "newmtl a\nKd 1 x 1\n")])) {
+        Err(ObjError::Parse{path, line, ..}) => {
+            assert!(path.ends_with("bad.mtl"));
+            assert_eq!(line, 2);
+        }
+        Err(e) => panic!("expected a parse error, got {}", e),
+        Ok(_) => panic!("expected a parse error")
+    }
+}
+
+#[test]
+fn uvs_and_normals_carry_over(){
+    let src = QUAD.to_owned() + "vt 0 0\nvt 1 0\nvt 1 1\nvn 0 0 2\nf 1/1/1 2/2/1 3/3/1\n";
+    let tris = load("vt_vn", &src).unwrap();
+    assert_eq!(tris[0].uvs, Some([(0.0, 0.0), (1.0, 0.0), (1.0, 1.0)]));
+    let normals = tris[0].normals.expect("normals should be set");
+    for n in normals {
+        assert_point(n, p(0.0, 0.0, 1.0));
+    }
+}
@@ -0,0 +1,101 @@
+// Bill Derksen - 10/26
+//-- tile renderer tests: thread count and tile size must not change a single bit of the image, and
+//-- every sample of a pixel is a ray of its own
+
+use std::path::Path;
+use std::sync::Mutex;
+
+use rusty_tracer::film::{Film};
+use rusty_tracer::integrator::{self, Integrator};
+use rusty_tracer::ray::{Ray};
+use rusty_tracer::sampler::{Rng};
+use rusty_tracer::render::{self, RenderSettings};
+use rusty_tracer::scene::{Scene};
+use rusty_tracer::vmaths::{Point};
+
+//-- odd size so edge tiles come out ragged
+const W: u32 = 37;
+const H: u32 = 21;
+
+fn render_with(scene: &Scene, integrator_name: &str, threads: usize, tile_size: u32) -> Film{
+    let integrator = integrator::by_name(integrator_name, scene, 5).expect("known integrator");
+    let settings = RenderSettings{width: W, height: H, threads, samples: 4, tile_size};
+    let mut tiles = 0;
+    let film = render::render(scene, &settings, integrator.as_ref(), &mut || tiles += 1);
+    assert_eq!(tiles, render::tile_count(&settings), "one callback per tile");
+    film
+}
+
+fn assert_same_bits(a: &Film, b: &Film, what: &str){
+    assert_eq!((a.width, a.height), (b.width, b.height));
+    for (i, (p, q)) in a.pixels.iter().zip(b.pixels.iter()).enumerate() {
+        let same = p.x.to_bits() == q.x.to_bits() && p.y.to_bits() == q.y.to_bits() && p.z.to_bits() == q.z.to_bits();
+        assert!(same, "{}: pixel ({}, {}) differs", what, i as u32 % a.width, i as u32 / a.width);
+    }
+}
+
+fn check_scene(path: &str, integrator_name: &str){
+    let scene = Scene::load(Path::new(path)).expect("shipped scene should load");
+    let single = render_with(&scene, integrator_name, 1, 32);
+    assert!(single.pixels.iter().any(|p| p.x + p.y + p.z > 0.0), "{}: image should not be black", path);
+
+    for (threads, tile_size) in [(4, 32), (3, 1), (8, 5), (2, 64)] {
+        let multi = render_with(&scene, integrator_name, threads, tile_size);
+        assert_same_bits(&single, &multi, &format!("{} with {} threads, {} px tiles", path, threads, tile_size));
+    }
+}
+
+#[test]
+fn phong_matches_single_thread(){
+    check_scene("scenes/cbox_sphere.scene", "phong");
+}
+
+#[test]
+fn path_tracer_matches_single_thread(){
+    //- random per sample, so this checks each pixel draws from its own rng whoever renders it
+    check_scene("scenes/cbox_area.scene", "path");
+}
+
+//-- integrator that remembers the rays it was asked about
+struct Recorder{
+    rays: Mutex<Vec<Ray>>
+
+} impl Integrator for Recorder {
+    fn radiance(&self, ray: &Ray, _scene: &Scene, _rng: &mut Rng) -> Point{
+        self.rays.lock().unwrap().push(Ray{origin: ray.origin, dir: ray.dir});
+        Point::default()
+    }
+}
+
+#[test]
+fn samples_are_distinct_rays(){
+    //- a deterministic integrator gains nothing from tracing the same ray again, so each sample must move
+    let scene = Scene::load(Path::new("scenes/cbox_sphere.scene")).expect("shipped scene should load");
+    let samples = 9;
+    let recorder = Recorder{rays: Mutex::new(Vec::new())};
+    let settings = RenderSettings{width: W, height: H, threads: 1, samples, tile_size: 32};
+    render::render_pixel(&scene, &settings, &recorder, 10, 7);
+
+    let rays = recorder.rays.into_inner().unwrap();
+    assert_eq!(rays.len(), samples as usize);
+    for (i, a) in rays.iter().enumerate() {
+        for b in &rays[i + 1..] {
+            assert!((a.dir.unit() - b.dir.unit()).mag() > 1e-12, "two samples share a direction");
+        }
+    }
+}
+
+#[test]
+fn single_sample_goes_through_pixel_center(){
+    let scene = Scene::load(Path::new("scenes/cbox_sphere.scene")).expect("shipped scene should load");
+    let recorder = Recorder{rays: Mutex::new(Vec::new())};
+    let settings = RenderSettings{width: W, height: H, threads: 1, samples: 1, tile_size: 32};
+    let (x, y) = (10, 7);
+    render::render_pixel(&scene, &settings, &recorder, x, y);
+
+    let rays = recorder.rays.into_inner().unwrap();
+    assert_eq!(rays.len(), 1);
+    let center = scene.camera.get_ray((x as f64 + 0.5) / W as f64, (y as f64 + 0.5) / H as f64, &mut Rng::for_pixel(x, y));
+    assert!((rays[0].dir.unit() - center.dir.unit()).mag() < 1e-12, "ray should go through the pixel center");
+    assert!((rays[0].origin - center.origin).mag() < 1e-12);
+}
@@ -0,0 +1,99 @@
+// Bill Derksen - 10/26
+//-- scene file parser tests: errors point at the right line + column, the shipped scenes load
+
+use std::path::Path;
+
+use rusty_tracer::ray::{Ray};
+use rusty_tracer::scene::{Scene, SceneError};
+use rusty_tracer::vmaths::{Point};
+
+const EPS: f64 = 1e-9;
+
+fn p(x: f64, y: f64, z: f64) -> Point{
+    Point::gen(x, y, z)
+}
+
+//-- parse src expecting a parse error, return (line, col, msg)
+fn parse_err(src: &str) -> (usize, usize, String){
+    match Scene::parse(src) {
+        Err(SceneError::Parse{line, col, msg}) => (line, col, msg),
+        Err(e) => panic!("expected a parse error, got {}", e),
+        Ok(_) => panic!("expected a parse error, scene parsed")
+    }
+}
+
+#[test]
+fn unknown_directive_points_at_keyword(){
+    let (line, col, msg) = parse_err("# comment\n\n   cylinder  cen 0 0 0  r 1\n");
+    assert_eq!((line, col), (3, 4));
+    assert!(msg.contains("cylinder"), "message should name the directive: {}", msg);
+}
+
+#[test]
+fn missing_attribute_points_at_directive(){
+    let (line, col, msg) = parse_err("camera eye 0 0 -30\nsphere  cen 0 0 0\n");
+    assert_eq!((line, col), (2, 1));
+    assert!(msg.contains("`r`"), "message should name the missing attribute: {}", msg);
+}
+
+#[test]
+fn missing_value_points_at_its_key(){
+    let (line, col, msg) = parse_err("material chalk kd 0.6\n\nsphere  cen 0 0  r 1\n");
+    assert_eq!((line, col), (3, 9));
+    assert!(msg.contains("`cen` expects 3"), "message should say how many values: {}", msg);
+
+    let (line, col, _msg) = parse_err("sphere  r 1  cen 0 0\n");
+    assert_eq!((line, col), (1, 14));
+}
+
+#[test]
+fn bad_number_points_at_token(){
+    let (line, col, msg) = parse_err("sphere cen 0 0 0  r 1\nsphere cen 1 2 3  r one\n");
+    assert_eq!((line, col), (2, 21));
+    assert!(msg.contains("one"), "message should quote the bad token: {}", msg);
+}
+
+#[test]
+fn error_display_has_line_and_col(){
+    let err = Scene::parse("\nsphere cen 0 0 x  r 1\n").err().expect("should fail");
+    assert!(err.to_string().starts_with("line 2, col 16:"), "got '{}'", err);
+}
+
+#[test]
+fn cbox_sphere_loads(){
+    let scene = Scene::load(Path::new("scenes/cbox_sphere.scene")).expect("shipped scene should load");
+    assert_eq!(scene.objects.len(), 8);                 //-- 3 spheres + 5 walls
+    assert_eq!(scene.lights.len(), 1);
+    assert!((scene.camera.aspect() - 1.7778).abs() < EPS);
+
+    let bounds = scene.bounds();                         //-- walls, give or take the padding flat rects get
+    assert!((bounds.min - p(-8.0, -4.0, -4.0)).mag() < 1e-3 && (bounds.max - p(8.0, 4.0, 4.0)).mag() < 1e-3);
+
+    //- straight down the view axis: the front of the red sphere at (0, -1, -2), r 1.6
+    let ray = Ray{origin: p(0.0, 0.0, -30.0), dir: p(0.0, 0.0, 1.0)};
+    let t = scene.objects.iter().filter_map(|o| o.hits(&ray, 0.0, f64::INFINITY)).map(|h| h.t).reduce(f64::min).expect("should hit");
+    assert!((t - (28.0 - 1.56f64.sqrt())).abs() < EPS, "got t {}", t);
+}
+
+#[test]
+fn cbox_sphere_parses_the_same_as_its_text(){
+    let src = std::fs::read_to_string("scenes/cbox_sphere.scene").unwrap();
+    let loaded = Scene::load(Path::new("scenes/cbox_sphere.scene")).unwrap();
+    let parsed = Scene::parse(&src).unwrap();
+    assert_eq!(loaded.objects.len(), parsed.objects.len());
+    assert_eq!(loaded.lights.len(), parsed.lights.len());
+    for (a, b) in loaded.objects.iter().zip(parsed.objects.iter()) {
+        let (ba, bb) = (a.bounding_box(), b.bounding_box());
+        assert!((ba.min - bb.min).mag() < EPS && (ba.max - bb.max).mag() < EPS);
+    }
+}
+
+#[test]
+fn octaves_out_of_range_point_at_value(){
+    let (zero, high) = (parse_err("texture clouds fbm  octaves 0\n"), parse_err("texture clouds fbm  octaves 17\n"));
+    assert_eq!((zero.0, zero.1), (1, 29));
+    assert_eq!((high.0, high.1), (1, 29));
+    assert_eq!(zero.2, high.2);
+    assert!(zero.2.contains("from 1 to 16"), "message should give the range: {}", zero.2);
+    assert!(Scene::parse("texture clouds fbm  octaves 1\ntexture mist turbulence  octaves 16\n").is_ok());
+}
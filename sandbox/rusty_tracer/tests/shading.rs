@@ -0,0 +1,94 @@
+// Bill Derksen - 10/26
+//-- recursive Phong tests: the mirror blend never makes light out of nothing
+
+use rusty_tracer::ray::{Ray};
+use rusty_tracer::sampler::{Rng};
+use rusty_tracer::scene::{Scene};
+use rusty_tracer::shading::{closest_hit, phong_single_src, trace_ray, SHADOW_EPS};
+use rusty_tracer::vmaths::{Point, Vec3};
+
+const EPS: f64 = 1e-12;
+const MAX_DEPTH: u32 = 5;
+
+fn p(x: f64, y: f64, z: f64) -> Point{
+    Point::gen(x, y, z)
+}
+
+//-- a sphere made of material m (given as its attributes) at the origin, with a lit wall behind the camera for it to reflect
+fn scene(mat: &str, with_wall: bool) -> Scene{
+    let mut src = format!("material m {}\nlight point pos 0 3 -5  id 4 4 4  is 4 4 4\nsphere cen 0 0 0  r 1  mat m\n", mat);
+    if with_wall {
+        src += "material wall  kd 0.5  ks 0.2  alpha 10  color 0.2 0.6 0.3\nxyrect z -20  x -50 50  y -50 50  mat wall\n";
+    }
+    Scene::parse(&src).expect("test scene should parse")
+}
+
+//-- ray at the sphere's front, a little off center so the mirror bounce comes back at an angle
+fn eye_ray() -> Ray{
+    Ray{origin: p(0.0, 0.0, -10.0), dir: p(0.0, 0.3, 10.0).unit()}
+}
+
+fn trace(scene: &Scene, ray: &Ray) -> Point{
+    trace_ray(ray, scene, &mut Rng::for_pixel(0, 0), 0, MAX_DEPTH)
+}
+
+//-- (Phong light at the hit, what the mirror bounce brings back) for eye_ray
+//- one rng drawn from in the same order as trace_ray, since a glowing sphere is also an area light
+fn parts(scene: &Scene) -> (Point, Point){
+    let ray = eye_ray();
+    let mut rng = Rng::for_pixel(0, 0);
+    let hit = closest_hit(&ray, &scene.objects).expect("eye ray should hit the sphere");
+    let local = phong_single_src(&hit, ray.origin, scene, &mut rng);
+    let d: Vec3 = ray.dir.unit();
+    let n: Vec3 = hit.facing_norm();
+    let refl_ray = Ray{origin: hit.ip + SHADOW_EPS * n, dir: d - 2.0 * d.dot(n) * n};
+    let reflected = trace_ray(&refl_ray, scene, &mut rng, 1, MAX_DEPTH);
+    (local, reflected)
+}
+
+fn assert_point(a: Point, b: Point){
+    assert!((a - b).mag() < EPS, "expected <{}, {}, {}>, got <{}, {}, {}>", b.x, b.y, b.z, a.x, a.y, a.z);
+}
+
+#[test]
+fn perfect_mirror_returns_only_what_it_reflects(){
+    let s = scene("kd 0.3  ks 0.5  alpha 20  color 0.8 0.2 0.2  refl 1", true);
+    let (local, reflected) = parts(&s);
+    assert!(local.x > 0.0 && reflected.mag() > 0.0);
+    assert_point(trace(&s, &eye_ray()), reflected);
+
+    //- nothing to reflect: black, none of the ambient or base color leaks through
+    let s = scene("kd 0.3  ks 0.5  alpha 20  color 0.8 0.2 0.2  refl 1", false);
+    assert_point(trace(&s, &eye_ray()), p(0.0, 0.0, 0.0));
+}
+
+#[test]
+fn partial_mirror_stays_between_local_and_reflected(){
+    for r in [0.0, 0.25, 0.5, 0.9] {
+        let s = scene(&format!("kd 0.3  ks 0.5  alpha 20  color 0.8 0.2 0.2  refl {}", r), true);
+        let (local, reflected) = parts(&s);
+        let out = trace(&s, &eye_ray());
+        assert_point(out, (1.0 - r) * local + r * reflected);
+        for (o, (a, b)) in [(out.x, (local.x, reflected.x)), (out.y, (local.y, reflected.y)), (out.z, (local.z, reflected.z))] {
+            assert!(o <= a.max(b) + EPS && o >= a.min(b) - EPS, "r {}: {} outside [{}, {}]", r, o, a.min(b), a.max(b));
+        }
+    }
+}
+
+#[test]
+fn emission_is_added_outside_the_blend(){
+    let glow = p(0.5, 0.25, 0.125);
+    let s = scene("color 0.8 0.2 0.2  refl 1  emit 0.5 0.25 0.125", false);
+    assert_point(trace(&s, &eye_ray()), glow);
+
+    let s = scene("kd 0.3  ks 0.5  alpha 20  color 0.8 0.2 0.2  refl 0.5  emit 0.5 0.25 0.125", true);
+    let (local, reflected) = parts(&s);
+    assert_point(trace(&s, &eye_ray()), glow + 0.5 * local + 0.5 * reflected);
+}
+
+#[test]
+fn depth_limit_falls_back_to_local(){
+    let s = scene("kd 0.3  ks 0.5  alpha 20  color 0.8 0.2 0.2  refl 1", true);
+    let (local, _) = parts(&s);
+    assert_point(trace_ray(&eye_ray(), &s, &mut Rng::for_pixel(0, 0), MAX_DEPTH, MAX_DEPTH), local);
+}
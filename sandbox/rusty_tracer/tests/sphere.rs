@@ -0,0 +1,164 @@
+// Bill Derksen - 10/26
+//-- ray-sphere intersection tests
+
+use rusty_tracer::geometry::{Sphere};
+use rusty_tracer::hittable::{Hittable};
+use rusty_tracer::ray::{Ray};
+use rusty_tracer::vmaths::{Point, Vec3};
+
+const EPS: f64 = 1e-9;
+
+fn sphere(cen: Point, r: f64) -> Sphere{
+    Sphere{cen, r, ..Sphere::default()}
+}
+
+fn ray(origin: Point, dir: Vec3) -> Ray{
+    Ray{origin, dir}
+}
+
+fn p(x: f64, y: f64, z: f64) -> Point{
+    Point::gen(x, y, z)
+}
+
+fn assert_close(a: f64, b: f64){
+    assert!((a - b).abs() < EPS, "expected {}, got {}", b, a);
+}
+
+fn assert_point(a: Point, b: Point){
+    assert!((a - b).mag() < EPS, "expected <{}, {}, {}>, got <{}, {}, {}>", b.x, b.y, b.z, a.x, a.y, a.z);
+}
+
+#[test]
+fn hit_from_outside_takes_near_side(){
+    let s = sphere(p(0.0, 0.0, 0.0), 1.0);
+    let hit = s.hits(&ray(p(0.0, 0.0, -5.0), p(0.0, 0.0, 1.0)), 0.0, f64::INFINITY).expect("should hit");
+    assert_close(hit.t, 4.0);
+    assert_point(hit.ip, p(0.0, 0.0, -1.0));
+    assert_point(hit.norm, p(0.0, 0.0, -1.0));
+    assert!(hit.front_face);
+}
+
+#[test]
+fn miss_beside_the_sphere(){
+    let s = sphere(p(0.0, 0.0, 0.0), 1.0);
+    assert!(s.hits(&ray(p(0.0, 1.5, -5.0), p(0.0, 0.0, 1.0)), 0.0, f64::INFINITY).is_none());
+    assert!(s.hits(&ray(p(0.0, 0.0, -5.0), p(0.0, 1.0, 0.0)), 0.0, f64::INFINITY).is_none());
+}
+
+#[test]
+fn sphere_behind_the_ray_is_not_hit(){
+    let s = sphere(p(0.0, 0.0, 0.0), 1.0);
+    assert!(s.hits(&ray(p(0.0, 0.0, 5.0), p(0.0, 0.0, 1.0)), 0.0, f64::INFINITY).is_none());
+}
+
+#[test]
+fn ray_from_inside_hits_exit_point(){
+    let s = sphere(p(0.0, 0.0, 0.0), 2.0);
+    let hit = s.hits(&ray(p(0.0, 0.0, 0.0), p(1.0, 0.0, 0.0)), 0.0, f64::INFINITY).expect("should hit");
+    assert_close(hit.t, 2.0);
+    assert_point(hit.ip, p(2.0, 0.0, 0.0));
+    assert_point(hit.norm, p(1.0, 0.0, 0.0));              //-- still outward
+    assert!(!hit.front_face);
+    assert_point(hit.facing_norm(), p(-1.0, 0.0, 0.0));
+}
+
+#[test]
+fn ray_from_inside_off_center(){
+    let s = sphere(p(1.0, 2.0, 3.0), 1.0);
+    let hit = s.hits(&ray(p(1.0, 2.5, 3.0), p(0.0, 1.0, 0.0)), 0.0, f64::INFINITY).expect("should hit");
+    assert_close(hit.t, 0.5);
+    assert_point(hit.ip, p(1.0, 3.0, 3.0));
+}
+
+#[test]
+fn tangent_ray_grazes_one_point(){
+    let s = sphere(p(0.0, 0.0, 0.0), 1.0);
+    let hit = s.hits(&ray(p(-5.0, 1.0, 0.0), p(1.0, 0.0, 0.0)), 0.0, f64::INFINITY).expect("tangent ray should hit");
+    assert_close(hit.t, 5.0);
+    assert_point(hit.ip, p(0.0, 1.0, 0.0));
+    assert_point(hit.norm, p(0.0, 1.0, 0.0));
+}
+
+#[test]
+fn near_tangent_ray_hits_the_near_root(){
+    //-- the old solver treated small discriminants as tangent hits and returned the midpoint
+    let s = sphere(p(0.0, 0.0, 0.0), 1.0);
+    let y: f64 = 0.999;
+    let hit = s.hits(&ray(p(-5.0, y, 0.0), p(1.0, 0.0, 0.0)), 0.0, f64::INFINITY).expect("should hit");
+    let x = -(1.0 - y * y).sqrt();
+    assert_close(hit.t, 5.0 + x);
+    assert_point(hit.ip, p(x, y, 0.0));
+}
+
+#[test]
+fn t_is_in_units_of_the_ray_direction(){
+    let s = sphere(p(0.0, 0.0, 0.0), 1.0);
+    let hit = s.hits(&ray(p(0.0, 0.0, -5.0), p(0.0, 0.0, 4.0)), 0.0, f64::INFINITY).expect("should hit");
+    assert_close(hit.t, 1.0);
+    assert_point(hit.ip, p(0.0, 0.0, -1.0));
+}
+
+#[test]
+fn normal_is_unit_length(){
+    for &(r, dir) in &[(0.25, p(1.0, 0.3, 0.2)), (1.0, p(1.0, 0.0, 0.0)), (40.0, p(1.0, -0.1, 0.05))] {
+        let s = sphere(p(0.5, -0.5, 2.0), r);
+        let origin = p(0.5, -0.5, 2.0) - (3.0 * r) * dir.unit();
+        let hit = s.hits(&ray(origin, dir), 0.0, f64::INFINITY).expect("should hit");
+        assert_close(hit.norm.mag(), 1.0);
+        assert_close((hit.ip - s.cen).mag(), r);
+    }
+}
+
+#[test]
+fn t_max_cuts_off_the_hit(){
+    let s = sphere(p(0.0, 0.0, 0.0), 1.0);
+    let r = ray(p(0.0, 0.0, -5.0), p(0.0, 0.0, 1.0));
+    assert!(s.hits(&r, 0.0, 3.0).is_none());
+    assert!(s.hits(&r, 0.0, 4.0).is_none());                //-- range is open at both ends
+    assert!(s.hits(&r, 0.0, 4.5).is_some());
+}
+
+#[test]
+fn t_min_past_the_near_root_gives_the_far_root(){
+    let s = sphere(p(0.0, 0.0, 0.0), 1.0);
+    let hit = s.hits(&ray(p(0.0, 0.0, -5.0), p(0.0, 0.0, 1.0)), 4.5, f64::INFINITY).expect("should hit far side");
+    assert_close(hit.t, 6.0);
+    assert!(!hit.front_face);
+}
+
+#[test]
+fn ray_leaving_the_surface_does_not_hit_it_again(){
+    let s = sphere(p(0.0, 0.0, 0.0), 1.0);
+    assert!(s.hits(&ray(p(0.0, 0.0, -1.0), p(0.0, 0.0, -1.0)), 1e-6, f64::INFINITY).is_none());
+}
+
+#[test]
+fn ray_entering_from_the_surface_hits_the_far_side(){
+    let s = sphere(p(0.0, 0.0, 0.0), 1.0);
+    let hit = s.hits(&ray(p(0.0, 0.0, -1.0), p(0.0, 0.0, 1.0)), 1e-6, f64::INFINITY).expect("should hit");
+    assert_close(hit.t, 2.0);
+    assert_point(hit.ip, p(0.0, 0.0, 1.0));
+}
+
+#[test]
+fn zero_direction_never_hits(){
+    let s = sphere(p(0.0, 0.0, 0.0), 1.0);
+    assert!(s.hits(&ray(p(0.0, 0.0, -5.0), p(0.0, 0.0, 0.0)), 0.0, f64::INFINITY).is_none());
+}
+
+#[test]
+fn hits_any_agrees_with_hits(){
+    let s = sphere(p(0.0, 0.0, 0.0), 1.0);
+    let r = ray(p(0.0, 0.0, -5.0), p(0.0, 0.0, 1.0));
+    assert!(s.hits_any(&r, 0.0, f64::INFINITY));
+    assert!(!s.hits_any(&r, 0.0, 3.0));
+    assert!(!s.hits_any(&ray(p(0.0, 0.0, 5.0), p(0.0, 0.0, 1.0)), 0.0, f64::INFINITY));
+}
+
+#[test]
+fn bounding_box_contains_the_sphere(){
+    let s = sphere(p(1.0, -2.0, 3.0), 0.5);
+    let b = s.bounding_box();
+    assert_point(b.min, p(0.5, -2.5, 2.5));
+    assert_point(b.max, p(1.5, -1.5, 3.5));
+}
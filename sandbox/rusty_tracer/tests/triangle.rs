@@ -0,0 +1,137 @@
+// Bill Derksen - 10/26
+//-- ray-triangle intersection tests
+
+use rusty_tracer::geometry::{Triangle};
+use rusty_tracer::hittable::{Hittable};
+use rusty_tracer::ray::{Ray};
+use rusty_tracer::vmaths::{Point, Vec3};
+
+const EPS: f64 = 1e-9;
+
+//-- unit right triangle in the z = 0 plane, counter-clockwise seen from +z, so its normal is +z
+fn tri() -> Triangle{
+    Triangle::gen(p(0.0, 0.0, 0.0), p(1.0, 0.0, 0.0), p(0.0, 1.0, 0.0))
+}
+
+fn ray(origin: Point, dir: Vec3) -> Ray{
+    Ray{origin, dir}
+}
+
+fn p(x: f64, y: f64, z: f64) -> Point{
+    Point::gen(x, y, z)
+}
+
+//-- straight down onto the triangle's plane from above (x, y)
+fn down_at(x: f64, y: f64) -> Ray{
+    ray(p(x, y, 2.0), p(0.0, 0.0, -1.0))
+}
+
+fn assert_close(a: f64, b: f64){
+    assert!((a - b).abs() < EPS, "expected {}, got {}", b, a);
+}
+
+fn assert_point(a: Point, b: Point){
+    assert!((a - b).mag() < EPS, "expected <{}, {}, {}>, got <{}, {}, {}>", b.x, b.y, b.z, a.x, a.y, a.z);
+}
+
+#[test]
+fn hit_inside(){
+    let t = tri();
+    let hit = t.hits(&down_at(0.25, 0.25), 0.0, f64::INFINITY).expect("should hit");
+    assert_close(hit.t, 2.0);
+    assert_point(hit.ip, p(0.25, 0.25, 0.0));
+    assert_point(hit.norm, p(0.0, 0.0, 1.0));
+    assert_point(hit.geo_norm, p(0.0, 0.0, 1.0));
+    assert!(hit.front_face);
+}
+
+#[test]
+fn miss_outside(){
+    assert!(tri().hits(&down_at(0.75, 0.75), 0.0, f64::INFINITY).is_none());
+    assert!(tri().hits(&down_at(-0.1, 0.5), 0.0, f64::INFINITY).is_none());
+    assert!(tri().hits(&down_at(0.5, -0.1), 0.0, f64::INFINITY).is_none());
+}
+
+#[test]
+fn hit_on_edges(){
+    let t = tri();
+    for (x, y) in [(0.5, 0.0), (0.0, 0.5), (0.5, 0.5)] {
+        let hit = t.hits(&down_at(x, y), 0.0, f64::INFINITY);
+        assert!(hit.is_some(), "edge point ({}, {}) should hit", x, y);
+    }
+}
+
+#[test]
+fn hit_on_vertices(){
+    let t = tri();
+    for v in [t.v0, t.v1, t.v2] {
+        let hit = t.hits(&down_at(v.x, v.y), 0.0, f64::INFINITY).expect("vertex should hit");
+        assert_point(hit.ip, v);
+    }
+}
+
+#[test]
+fn parallel_ray_misses(){
+    assert!(tri().hits(&ray(p(-1.0, 0.25, 0.0), p(1.0, 0.0, 0.0)), 0.0, f64::INFINITY).is_none());
+    assert!(tri().hits(&ray(p(-1.0, 0.25, 0.5), p(1.0, 0.0, 0.0)), 0.0, f64::INFINITY).is_none());
+}
+
+#[test]
+fn back_face_hit(){
+    let t = tri();
+    let hit = t.hits(&ray(p(0.25, 0.25, -3.0), p(0.0, 0.0, 1.0)), 0.0, f64::INFINITY).expect("back faces still hit");
+    assert_close(hit.t, 3.0);
+    assert_point(hit.norm, p(0.0, 0.0, 1.0));
+    assert!(!hit.front_face);
+    assert_point(hit.facing_norm(), p(0.0, 0.0, -1.0));
+}
+
+#[test]
+fn behind_origin_misses(){
+    assert!(tri().hits(&ray(p(0.25, 0.25, 2.0), p(0.0, 0.0, 1.0)), 0.0, f64::INFINITY).is_none());
+}
+
+#[test]
+fn respects_t_bounds(){
+    let r = down_at(0.25, 0.25);
+    assert!(tri().hits(&r, 0.0, 1.5).is_none());
+    assert!(tri().hits(&r, 2.5, f64::INFINITY).is_none());
+    assert!(tri().hits(&r, 0.0, 2.0).is_none());           //-- bounds are exclusive
+    assert!(tri().hits(&r, 2.0, 3.0).is_none());
+    assert!(tri().hits(&r, 1.9, 2.1).is_some());
+}
+
+#[test]
+fn barycentrics_weight_the_vertices(){
+    let t = tri();
+    for (x, y) in [(0.25, 0.25), (0.1, 0.7), (0.6, 0.3), (0.0, 0.0), (0.5, 0.5)] {
+        let hit = t.hits(&down_at(x, y), 0.0, f64::INFINITY).expect("should hit");
+        let (u, v) = hit.bary;
+        let w = 1.0 - u - v;
+        for weight in [w, u, v] {
+            assert!((-EPS..=1.0 + EPS).contains(&weight), "weight {} out of [0, 1]", weight);
+        }
+        assert_close(w + u + v, 1.0);
+        assert_point(w * t.v0 + u * t.v1 + v * t.v2, hit.ip);
+    }
+}
+
+#[test]
+fn vertex_normals_shade_but_keep_geometric_normal(){
+    let mut t = tri();
+    let tilt = p(1.0, 0.0, 1.0).unit();
+    t.normals = Some([tilt, tilt, tilt]);
+    let hit = t.hits(&down_at(0.25, 0.25), 0.0, f64::INFINITY).expect("should hit");
+    assert_point(hit.norm, tilt);
+    assert_point(hit.geo_norm, p(0.0, 0.0, 1.0));
+    assert!(hit.front_face);
+}
+
+#[test]
+fn tilted_triangle(){
+    let t = Triangle::gen(p(1.0, 0.0, 0.0), p(0.0, 1.0, 0.0), p(0.0, 0.0, 1.0));
+    let hit = t.hits(&ray(p(0.0, 0.0, 0.0), p(1.0, 1.0, 1.0)), 0.0, f64::INFINITY).expect("should hit");
+    assert_point(hit.ip, p(1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0));
+    assert_point(hit.geo_norm, p(1.0, 1.0, 1.0).unit());
+    assert!(!hit.front_face);
+}
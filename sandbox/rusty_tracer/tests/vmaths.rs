@@ -0,0 +1,32 @@
+// Bill Derksen - 10/26
+//-- vector math tests
+
+use rusty_tracer::vmaths::{Point, Vec3};
+
+const EPS: f64 = 1e-9;
+
+fn p(x: f64, y: f64, z: f64) -> Point{
+    Point::gen(x, y, z)
+}
+
+fn assert_point(a: Point, b: Point){
+    assert!((a - b).mag() < EPS, "expected <{}, {}, {}>, got <{}, {}, {}>", b.x, b.y, b.z, a.x, a.y, a.z);
+}
+
+#[test]
+fn cross_of_axes_is_right_handed(){
+    let (x, y, z): (Vec3, Vec3, Vec3) = (p(1.0, 0.0, 0.0), p(0.0, 1.0, 0.0), p(0.0, 0.0, 1.0));
+    assert_point(x.cross(y), z);
+    assert_point(y.cross(z), x);
+    assert_point(z.cross(x), y);
+    assert_point(y.cross(x), -z);
+}
+
+#[test]
+fn cross_is_perpendicular_to_both(){
+    let (a, b) = (p(1.0, 2.0, 3.0), p(-4.0, 0.5, 2.0));
+    let c = a.cross(b);
+    assert_point(c, p(2.5, -14.0, 8.5));
+    assert!(c.dot(a).abs() < EPS && c.dot(b).abs() < EPS);
+    assert_point(a.cross(a), p(0.0, 0.0, 0.0));
+}